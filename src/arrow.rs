@@ -0,0 +1,225 @@
+use std::io::{BufWriter, Write};
+
+use anyhow::{Context, Result};
+use arrow_array::RecordBatch;
+use arrow_ipc::writer::{FileWriter, StreamWriter};
+use mysql::{Column, Value};
+
+use crate::columnar::{BATCH_ROWS, BatchBuilder};
+use crate::{FormatWriter, OUTPUT_BUFFER_CAPACITY};
+
+/// Arrow IPC encoder over a buffered output, in either file or stream layout
+enum IpcEncoder<W: Write> {
+    File(FileWriter<BufWriter<W>>),
+    Stream(StreamWriter<BufWriter<W>>),
+}
+
+impl<W: Write> IpcEncoder<W> {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        match self {
+            Self::File(writer) => writer.write(batch)?,
+            Self::Stream(writer) => writer.write(batch)?,
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        match self {
+            Self::File(writer) => {
+                writer.finish()?;
+                writer.get_mut().flush()?;
+            },
+            Self::Stream(writer) => {
+                writer.finish()?;
+                writer.get_mut().flush()?;
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Arrow IPC writer that implements the FormatWriter trait
+///
+/// Writes either the IPC file format (also known as Feather v2, readable with random access
+/// by `pyarrow.feather`, `polars.read_ipc` and `pandas.read_feather`) or the IPC stream
+/// format, which has no footer and can be consumed while it is being written. Columns are
+/// typed from MySQL column metadata (see [`crate::columnar`]) and rows are written in
+/// record batches as they stream in.
+pub struct ArrowIpcWriter<W: Write> {
+    output: Option<W>,
+    encoder: Option<IpcEncoder<W>>,
+    batch: Option<BatchBuilder>,
+    stream: bool,
+}
+
+impl<W: Write> ArrowIpcWriter<W> {
+    /// Creates a writer producing the Arrow IPC file (Feather v2) format
+    pub fn file(writer: W) -> Self {
+        Self::new(writer, false)
+    }
+
+    /// Creates a writer producing the Arrow IPC stream format
+    pub fn stream(writer: W) -> Self {
+        Self::new(writer, true)
+    }
+
+    fn new(writer: W, stream: bool) -> Self {
+        Self {
+            output: Some(writer),
+            encoder: None,
+            batch: None,
+            stream,
+        }
+    }
+
+    /// Writes the schema message once the columns are known
+    fn start(&mut self, batch: BatchBuilder) -> Result<()> {
+        let output = BufWriter::with_capacity(
+            OUTPUT_BUFFER_CAPACITY,
+            self.output.take().context("Arrow header has already been written")?,
+        );
+        let schema = batch.schema();
+
+        self.encoder = Some(if self.stream {
+            IpcEncoder::Stream(StreamWriter::try_new(output, &schema)?)
+        } else {
+            IpcEncoder::File(FileWriter::try_new(output, &schema)?)
+        });
+        self.batch = Some(batch);
+        Ok(())
+    }
+
+    /// Writes a record batch once enough rows have accumulated
+    fn write_batch_if_full(&mut self) -> Result<()> {
+        if self.batch.as_ref().is_some_and(|batch| batch.len() >= BATCH_ROWS) {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Writes any buffered rows as a record batch
+    fn write_batch(&mut self) -> Result<()> {
+        if let (Some(encoder), Some(batch)) = (self.encoder.as_mut(), self.batch.as_mut())
+            && batch.len() > 0
+        {
+            encoder.write(&batch.take_batch()?)?;
+        }
+        Ok(())
+    }
+
+    fn batch_mut(&mut self) -> Result<&mut BatchBuilder> {
+        self.batch.as_mut().context("Arrow header must be written before rows")
+    }
+}
+
+impl<W: Write> FormatWriter for ArrowIpcWriter<W> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        self.start(BatchBuilder::from_names(columns))
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        self.batch_mut()?.append_strings(row)?;
+        self.write_batch_if_full()
+    }
+
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        self.start(BatchBuilder::from_columns(columns)?)
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        self.batch_mut()?.append_values(values)?;
+        self.write_batch_if_full()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if self.encoder.is_none() && self.output.is_some() {
+            self.start(BatchBuilder::from_names(&[]))?;
+        }
+        self.write_batch()?;
+        if let Some(encoder) = self.encoder.as_mut() {
+            encoder.finish()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Date32Type, Int64Type};
+    use arrow_ipc::reader::{FileReader, StreamReader};
+    use arrow_schema::DataType;
+    use mysql::consts::ColumnType;
+    use std::io::Cursor;
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONGLONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+                .with_name(b"name")
+                .with_character_set(33),
+            Column::new(ColumnType::MYSQL_TYPE_DATE).with_name(b"born"),
+        ]
+    }
+
+    fn write_rows(writer: &mut ArrowIpcWriter<&mut Vec<u8>>, rows: i64) {
+        writer.write_columns(&columns()).unwrap();
+        for id in 0..rows {
+            writer
+                .write_values(&[
+                    Value::Int(id),
+                    Value::Bytes(b"Alice".to_vec()),
+                    Value::Bytes(b"1970-01-02".to_vec()),
+                ])
+                .unwrap();
+        }
+        writer.write_values(&[Value::NULL, Value::NULL, Value::NULL]).unwrap();
+    }
+
+    #[test]
+    fn test_arrow_file_round_trip() {
+        let mut output = Vec::new();
+        let mut writer = ArrowIpcWriter::file(&mut output);
+        write_rows(&mut writer, BATCH_ROWS as i64 + 1);
+        writer.finalize().unwrap();
+
+        let reader = FileReader::try_new(Cursor::new(output), None).unwrap();
+        assert_eq!(reader.num_batches(), 2);
+        assert_eq!(reader.schema().field(0).data_type(), &DataType::Int64);
+        assert_eq!(reader.schema().field(2).data_type(), &DataType::Date32);
+
+        let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches.iter().map(RecordBatch::num_rows).sum::<usize>(), BATCH_ROWS + 2);
+        assert_eq!(batches[0].column(0).as_primitive::<Int64Type>().value(7), 7);
+        assert_eq!(batches[0].column(1).as_string::<i32>().value(0), "Alice");
+        assert_eq!(batches[0].column(2).as_primitive::<Date32Type>().value(0), 1);
+        assert!(batches[1].column(0).is_null(1));
+    }
+
+    #[test]
+    fn test_arrow_stream_round_trip() {
+        let mut output = Vec::new();
+        let mut writer = ArrowIpcWriter::stream(&mut output);
+        write_rows(&mut writer, 2);
+        writer.finalize().unwrap();
+
+        let reader = StreamReader::try_new(Cursor::new(output), None).unwrap();
+        let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 3);
+    }
+
+    #[test]
+    fn test_arrow_empty_result_has_schema() {
+        let mut output = Vec::new();
+        let mut writer = ArrowIpcWriter::file(&mut output);
+        writer.write_columns(&columns()).unwrap();
+        writer.finalize().unwrap();
+
+        let reader = FileReader::try_new(Cursor::new(output), None).unwrap();
+        assert_eq!(reader.num_batches(), 0);
+        assert_eq!(reader.schema().fields().len(), 3);
+    }
+}
@@ -0,0 +1,368 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+use crate::cli::Cli;
+
+/// Job settings that take a path, resolved relative to the manifest
+const PATH_OPTIONS: &[&str] = &["query_file", "params_file", "output"];
+
+/// Job settings that are passed on as the command-line option of the same name
+const JOB_OPTIONS: &[&str] = &[
+    "query",
+    "query_file",
+    "params_file",
+    "output",
+    "format",
+    "pretty",
+    "null_marker",
+    "row_group_size",
+    "sql_table",
+    "sql_batch_size",
+    "compress",
+    "compression_level",
+    "split_rows",
+    "split_bytes",
+    "partition_by",
+    "max_open_partitions",
+    "result_sets",
+    "max_width",
+    "allow_empty",
+    "output_timezone",
+    "create_dirs",
+    "query_timeout",
+    "read_only",
+    "allow_writes",
+];
+
+/// Job settings that identify one job and so cannot be set in `[defaults]`
+const JOB_ONLY_OPTIONS: &[&str] = &["name", "query", "query_file", "output"];
+
+/// One job of a batch manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchJob {
+    /// Name shown in progress messages and the summary
+    pub name: String,
+    /// Command-line arguments equivalent to the job's settings
+    pub args: Vec<OsString>,
+}
+
+impl BatchJob {
+    /// Builds the options of the job, with the verbosity of the batch
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the job's settings are not valid options.
+    pub fn cli(&self, batch: &Cli) -> Result<Cli> {
+        let mut cli = Cli::try_parse_from(&self.args).map_err(|e| {
+            let message = e.to_string();
+            let first_line = message.lines().next().unwrap_or_default();
+            anyhow::anyhow!("Invalid settings: {}", first_line.trim_start_matches("error: "))
+        })?;
+        cli.verbose = batch.verbose;
+        cli.quiet = batch.quiet;
+        cli.query_timeout = cli.query_timeout.or(batch.query_timeout);
+        // --allow-writes before `batch` applies to jobs that do not ask for read-only mode
+        cli.allow_writes |= batch.allow_writes && !cli.read_only;
+        Ok(cli)
+    }
+}
+
+/// Loads the jobs of a batch manifest
+///
+/// ```toml
+/// [defaults]
+/// format = "csv"
+/// create_dirs = true
+///
+/// [[jobs]]
+/// name = "daily_users"
+/// query = "SELECT id, name FROM users WHERE created_at >= :since"
+/// params = { since = 2026-10-01 }
+/// output = "out/users-{date}.csv"
+///
+/// [[jobs]]
+/// query_file = "reports/orders.sql"
+/// output = "out/orders.json"
+/// format = "json"
+/// ```
+///
+/// Each `[[jobs]]` entry names a `query` or `query_file` and an `output`, plus any of the
+/// output options, spelled like the command-line options with underscores. `[defaults]` holds
+/// options for every job, which a job can override. `params` is a table of query parameters
+/// whose TOML types give the parameter types. Relative paths are relative to the manifest.
+///
+/// # Errors
+///
+/// Returns an error if the manifest cannot be read or parsed, has no jobs, or has unknown
+/// settings or values of the wrong type.
+pub fn load_manifest(path: &Path) -> Result<Vec<BatchJob>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read batch manifest {}: {}", path.display(), e))?;
+    let directory = path.parent().unwrap_or(Path::new(""));
+    parse_manifest(&text, directory).map_err(|e| anyhow::anyhow!("Invalid batch manifest {}: {}", path.display(), e))
+}
+
+/// Parses the text of a batch manifest, resolving relative paths against `directory`
+///
+/// # Errors
+///
+/// Returns an error if the manifest has no jobs, or has unknown settings or values of the
+/// wrong type.
+pub fn parse_manifest(text: &str, directory: &Path) -> Result<Vec<BatchJob>> {
+    let document: DocumentMut = text.parse()?;
+    for (key, _) in document.iter() {
+        if key != "defaults" && key != "jobs" {
+            bail!("unknown key '{}' (expected [defaults] and [[jobs]])", key);
+        }
+    }
+
+    let defaults = match document.get("defaults") {
+        Some(item) => match item.as_table_like() {
+            Some(table) => Some(table),
+            None => bail!("defaults must be a table"),
+        },
+        None => None,
+    };
+    if let Some(defaults) = defaults {
+        for (key, _) in defaults.iter() {
+            if JOB_ONLY_OPTIONS.contains(&key) {
+                bail!("{} cannot be set in [defaults]", key);
+            }
+        }
+    }
+
+    let jobs = match document.get("jobs") {
+        Some(Item::ArrayOfTables(jobs)) if !jobs.is_empty() => jobs,
+        Some(Item::ArrayOfTables(_)) | None => bail!("no [[jobs]] entries"),
+        Some(_) => bail!("jobs must be written as [[jobs]] entries"),
+    };
+
+    let mut parsed = Vec::with_capacity(jobs.len());
+    for (index, job) in jobs.iter().enumerate() {
+        let name = match job.get("name") {
+            Some(item) => match item.as_str() {
+                Some(name) if !name.trim().is_empty() => name.to_string(),
+                _ => bail!("name of job {} must be a non-empty string", index + 1),
+            },
+            None => format!("job {}", index + 1),
+        };
+        if !job.contains_key("query") && !job.contains_key("query_file") {
+            bail!("job '{}' needs a query or query_file", name);
+        }
+        if !job.contains_key("output") {
+            bail!("job '{}' needs an output", name);
+        }
+
+        let mut args = vec![OsString::from("gold_digger")];
+        let settings = defaults
+            .iter()
+            .flat_map(|defaults| defaults.iter())
+            .filter(|(key, _)| !job.contains_key(key))
+            .chain(job.iter());
+        for (key, item) in settings {
+            if key != "name" {
+                push_setting(&mut args, key, item, directory).map_err(|e| anyhow::anyhow!("job '{}': {}", name, e))?;
+            }
+        }
+        parsed.push(BatchJob { name, args });
+    }
+    Ok(parsed)
+}
+
+/// Appends the command-line arguments for one job setting
+fn push_setting(args: &mut Vec<OsString>, key: &str, item: &Item, directory: &Path) -> Result<()> {
+    if key == "params" {
+        let Some(params) = item.as_table_like() else {
+            bail!("params must be a table of parameter values");
+        };
+        return push_params(args, params);
+    }
+    if !JOB_OPTIONS.contains(&key) {
+        bail!("unknown setting '{}'", key);
+    }
+    let option = OsString::from(format!("--{}", key.replace('_', "-")));
+    let Some(value) = item.as_value() else {
+        bail!("{} must be a value, not a table", key);
+    };
+
+    match value {
+        Value::Boolean(flag) => {
+            if *flag.value() {
+                args.push(option);
+            }
+        },
+        Value::Array(array) => {
+            for value in array.iter() {
+                args.push(option.clone());
+                args.push(scalar(key, value)?.into());
+            }
+        },
+        Value::String(text) if PATH_OPTIONS.contains(&key) && text.value() != "-" => {
+            args.push(option);
+            args.push(directory.join(text.value()).into_os_string());
+        },
+        value => {
+            args.push(option);
+            args.push(scalar(key, value)?.into());
+        },
+    }
+    Ok(())
+}
+
+/// Appends a `--param` argument per query parameter, typed by its TOML type
+fn push_params(args: &mut Vec<OsString>, params: &dyn TableLike) -> Result<()> {
+    for (name, item) in params.iter() {
+        let param = match item.as_value() {
+            Some(Value::String(text)) => format!("{}={}", name, text.value()),
+            Some(Value::Integer(number)) => format!("{}:int={}", name, number.value()),
+            Some(Value::Float(number)) => format!("{}:float={}", name, number.value()),
+            Some(Value::Boolean(flag)) => format!("{}:int={}", name, u8::from(*flag.value())),
+            Some(Value::Datetime(datetime)) => {
+                let datetime = datetime.value();
+                match (datetime.date, datetime.time, datetime.offset) {
+                    (Some(_), None, None) => format!("{}:date={}", name, datetime),
+                    (Some(_), Some(_), None) => format!("{}:datetime={}", name, datetime),
+                    _ => bail!("parameter '{}' must be a local date or date-time, without a time zone offset", name),
+                }
+            },
+            _ => bail!("parameter '{}' must be a string, number, boolean, date or date-time", name),
+        };
+        args.push("--param".into());
+        args.push(param.into());
+    }
+    Ok(())
+}
+
+/// Returns a string or integer setting as an argument value
+fn scalar(key: &str, value: &Value) -> Result<String> {
+    match value {
+        Value::String(text) => Ok(text.value().clone()),
+        Value::Integer(number) => Ok(number.value().to_string()),
+        _ => bail!("{} must be a string or an integer", key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(job: &BatchJob) -> Vec<String> {
+        job.args
+            .iter()
+            .skip(1)
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = r#"
+[defaults]
+format = "csv"
+pretty = true
+allow_empty = false
+
+[[jobs]]
+name = "users"
+query = "SELECT * FROM users WHERE id = :id AND day >= :since"
+params = { id = 7, since = 2026-10-01, label = "x" }
+output = "out/users.csv"
+partition_by = ["region", "day"]
+
+[[jobs]]
+query_file = "orders.sql"
+output = "/abs/orders.json"
+format = "json"
+split_bytes = "100M"
+"#;
+        let jobs = parse_manifest(manifest, Path::new("jobs")).unwrap();
+        assert_eq!(jobs.len(), 2);
+
+        assert_eq!(jobs[0].name, "users");
+        assert_eq!(
+            args(&jobs[0]),
+            vec![
+                "--format",
+                "csv",
+                "--pretty",
+                "--query",
+                "SELECT * FROM users WHERE id = :id AND day >= :since",
+                "--param",
+                "id:int=7",
+                "--param",
+                "since:date=2026-10-01",
+                "--param",
+                "label=x",
+                "--output",
+                "jobs/out/users.csv",
+                "--partition-by",
+                "region",
+                "--partition-by",
+                "day",
+            ]
+        );
+
+        assert_eq!(jobs[1].name, "job 2");
+        assert_eq!(
+            args(&jobs[1]),
+            vec![
+                "--pretty",
+                "--query-file",
+                "jobs/orders.sql",
+                "--output",
+                "/abs/orders.json",
+                "--format",
+                "json",
+                "--split-bytes",
+                "100M",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_job_cli() {
+        let manifest = "[[jobs]]\nquery = \"SELECT 1\"\noutput = \"-\"\nformat = \"ndjson\"\ncompression_level = 3";
+        let jobs = parse_manifest(manifest, Path::new("")).unwrap();
+        let batch = Cli::parse_from(["gold_digger", "-vv", "batch", "jobs.toml"]);
+
+        let cli = jobs[0].cli(&batch).unwrap();
+        assert_eq!(cli.query.as_deref(), Some("SELECT 1"));
+        assert_eq!(cli.output.as_deref(), Some(Path::new("-")));
+        assert_eq!(cli.compression_level, Some(3));
+        assert_eq!(cli.verbose, 2);
+
+        let manifest = "[[jobs]]\nquery = \"SELECT 1\"\noutput = \"out.csv\"\nformat = \"yaml\"";
+        let jobs = parse_manifest(manifest, Path::new("")).unwrap();
+        let error = jobs[0].cli(&batch).err().unwrap().to_string();
+        assert!(error.starts_with("Invalid settings: invalid value 'yaml'"), "{}", error);
+    }
+
+    #[test]
+    fn test_invalid_manifests() {
+        let cases = [
+            ("", "no [[jobs]] entries"),
+            ("jobs = 1", "jobs must be written as [[jobs]] entries"),
+            ("[job]\nquery = \"SELECT 1\"", "unknown key 'job'"),
+            ("[[jobs]]\noutput = \"a.csv\"", "job 'job 1' needs a query or query_file"),
+            ("[[jobs]]\nname = \"a\"\nquery = \"SELECT 1\"", "job 'a' needs an output"),
+            ("[defaults]\noutput = \"a.csv\"\n[[jobs]]\nquery = \"SELECT 1\"", "output cannot be set in [defaults]"),
+            ("[[jobs]]\nquery = \"SELECT 1\"\noutput = \"a.csv\"\ndb_url = \"mysql://x\"", "unknown setting 'db_url'"),
+            (
+                "[[jobs]]\nquery = \"SELECT 1\"\noutput = \"a.csv\"\nformat = 1.5",
+                "format must be a string or an integer",
+            ),
+            (
+                "[[jobs]]\nquery = \"SELECT :at\"\noutput = \"a.csv\"\nparams = { at = 2026-10-01T08:00:00Z }",
+                "without a time zone offset",
+            ),
+        ];
+        for (manifest, message) in cases {
+            let error = parse_manifest(manifest, Path::new("")).unwrap_err().to_string();
+            assert!(error.contains(message), "{:?}: {}", manifest, error);
+        }
+    }
+}
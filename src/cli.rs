@@ -11,6 +11,14 @@ pub struct Cli {
     #[arg(long, env = "DATABASE_URL")]
     pub db_url: Option<String>,
 
+    /// Connection profile to use from the configuration file, e.g. prod for [profiles.prod]
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Configuration file with connection profiles [default: $XDG_CONFIG_HOME/gold_digger.toml]
+    #[arg(long, value_name = "FILE", requires = "profile")]
+    pub config: Option<PathBuf>,
+
     /// SQL query string
     #[arg(short = 'q', long, conflicts_with = "query_file")]
     pub query: Option<String>,
@@ -19,10 +27,26 @@ pub struct Cli {
     #[arg(long, conflicts_with = "query")]
     pub query_file: Option<PathBuf>,
 
-    /// Output file path
+    /// Query parameter for a :NAME or ? placeholder (numbered from 1), as NAME[:TYPE]=VALUE with TYPE string (default), int, float, date, datetime or null (repeatable)
+    #[arg(long = "param", value_name = "NAME[:TYPE]=VALUE")]
+    pub params: Vec<crate::params::QueryParam>,
+
+    /// JSON file of query parameters: an object of named values, or an array of values for ? placeholders
+    #[arg(long, value_name = "FILE")]
+    pub params_file: Option<PathBuf>,
+
+    /// Output file path, or '-' to write to stdout (requires --format); may contain {date}, {datetime}, {epoch}, {query_name}, {param.NAME} and {hostname} placeholders
     #[arg(short, long, env = "OUTPUT_FILE")]
     pub output: Option<PathBuf>,
 
+    /// Time zone of the {date} and {datetime} output path placeholders [default: local]
+    #[arg(long, value_enum, value_name = "ZONE")]
+    pub output_timezone: Option<OutputTimezone>,
+
+    /// Create missing parent directories of the output path
+    #[arg(long)]
+    pub create_dirs: bool,
+
     /// Output format override
     #[arg(long, value_enum)]
     pub format: Option<OutputFormat>,
@@ -39,6 +63,83 @@ pub struct Cli {
     #[arg(long)]
     pub pretty: bool,
 
+    /// String written for SQL NULL in CSV, TSV, Markdown and HTML output (table and vertical show NULL), e.g. '\N' or 'NULL' [default: empty]
+    #[arg(long, value_name = "MARKER")]
+    pub null_marker: Option<String>,
+
+    /// Rows per row group in Parquet output [default: 131072]
+    #[arg(long, value_name = "ROWS", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub row_group_size: Option<usize>,
+
+    /// Target table for SQL output, optionally as db.table [default: output file name]
+    #[arg(long, value_name = "TABLE")]
+    pub sql_table: Option<String>,
+
+    /// Rows per INSERT statement in SQL output [default: 100]
+    #[arg(long, value_name = "ROWS", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub sql_batch_size: Option<usize>,
+
+    /// Compress output [default: from the file extension, e.g. .csv.gz or .json.zst]
+    #[arg(long, value_enum, value_name = "ALGORITHM")]
+    pub compress: Option<Compression>,
+
+    /// Compression level (gzip: 0-9, zstd: 1-22) [default: 6 for gzip, 3 for zstd]
+    #[arg(long, value_name = "LEVEL", allow_negative_numbers = true)]
+    pub compression_level: Option<i32>,
+
+    /// Split output into numbered parts of at most N rows each (csv, tsv, json)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub split_rows: Option<u64>,
+
+    /// Split output into numbered parts of about SIZE bytes each, e.g. 100M or 1G (csv, tsv, json)
+    #[arg(long, value_name = "SIZE", value_parser = crate::split::parse_size)]
+    pub split_bytes: Option<u64>,
+
+    /// Write one file per distinct value of these columns, in Hive-style COLUMN=VALUE directories
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        value_delimiter = ',',
+        conflicts_with_all = ["split_rows", "split_bytes"]
+    )]
+    pub partition_by: Vec<String>,
+
+    /// Maximum partition files open at once with --partition-by [default: 64]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..), requires = "partition_by")]
+    pub max_open_partitions: Option<u64>,
+
+    /// How to write a query that returns several result sets: numbered files (out.1.csv, out.2.csv) or one JSON document with an array per set [default: numbered]
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub result_sets: Option<ResultSetMode>,
+
+    /// Maximum cell width in table output, for every column or as COLUMN=WIDTH (repeatable)
+    #[arg(long, value_name = "[COLUMN=]WIDTH")]
+    pub max_width: Vec<crate::table::WidthLimit>,
+
+    /// Cancel the query if it runs longer than this, e.g. 30s, 5m or 1h (a bare number is seconds)
+    #[arg(long, value_name = "DURATION", value_parser = crate::timeout::parse_duration)]
+    pub query_timeout: Option<std::time::Duration>,
+
+    /// Give up on connecting to the server after this long, e.g. 10s [default: no limit]
+    #[arg(long, value_name = "DURATION", value_parser = crate::timeout::parse_duration)]
+    pub connect_timeout: Option<std::time::Duration>,
+
+    /// Retry connecting and read statements this many times after transient failures, such as a restarting server or a deadlock [default: 0]
+    #[arg(long, value_name = "N")]
+    pub retries: Option<u32>,
+
+    /// Wait before the first retry, doubled before each later one [default: 1s]
+    #[arg(long, value_name = "DURATION", value_parser = crate::timeout::parse_duration)]
+    pub retry_backoff: Option<std::time::Duration>,
+
+    /// Run the query in a read-only transaction and refuse statements other than SELECT, SHOW, WITH, EXPLAIN and CALL (the default)
+    #[arg(long, overrides_with = "allow_writes")]
+    pub read_only: bool,
+
+    /// Allow statements that change data, turning off --read-only
+    #[arg(long, overrides_with = "read_only")]
+    pub allow_writes: bool,
+
     /// Exit successfully on empty result sets
     #[arg(long)]
     pub allow_empty: bool,
@@ -62,6 +163,86 @@ pub enum Commands {
         #[arg(value_enum)]
         shell: Shell,
     },
+    /// Run every job of a TOML manifest over one database connection
+    Batch(BatchArgs),
+}
+
+/// Options of the batch subcommand
+#[derive(Args, Debug, Clone)]
+pub struct BatchArgs {
+    /// Manifest of [[jobs]], each with a query or query_file, an output and output options
+    #[arg(value_name = "MANIFEST")]
+    pub manifest: PathBuf,
+
+    /// Stop at the first job that fails or finds no rows, skipping the rest (default)
+    #[arg(long, conflicts_with = "keep_going")]
+    pub fail_fast: bool,
+
+    /// Run every job even after one fails
+    #[arg(long)]
+    pub keep_going: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    // Requires the `gzip` feature
+    Gzip,
+    // Requires the `zstd` feature
+    Zstd,
+}
+
+impl Compression {
+    /// Determines the compression from the last extension of a path (`data.csv.gz`)
+    pub fn from_extension(path: &std::path::Path) -> Self {
+        path.extension()
+            .and_then(|s| s.to_str())
+            .and_then(crate::compression::compression_for_extension)
+            .unwrap_or(Self::None)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResultSetMode {
+    // One file per result set, numbered when there is more than one
+    #[default]
+    Numbered,
+    // Named arrays in a single JSON document
+    Combined,
+}
+
+impl ResultSetMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Numbered => "numbered",
+            Self::Combined => "combined",
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputTimezone {
+    // The machine's local time zone
+    #[default]
+    Local,
+    Utc,
+}
+
+impl OutputTimezone {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Utc => "utc",
+        }
+    }
 }
 
 #[derive(ValueEnum, Clone, Debug)]
@@ -89,20 +270,43 @@ pub struct TlsOptions {
     pub allow_invalid_certificate: bool,
 }
 
-#[derive(ValueEnum, Clone, Debug)]
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum OutputFormat {
     Csv,
     Json,
     Tsv,
+    // Newline-delimited JSON (JSON Lines), one object per row
+    #[value(alias = "jsonl")]
+    Ndjson,
+    // Apache Parquet, requires the `parquet` feature
+    Parquet,
+    // Arrow IPC file (Feather v2) and stream, require the `arrow` feature
+    Arrow,
+    ArrowStream,
+    // Excel workbook, requires the `xlsx` feature
+    Xlsx,
+    // SQL INSERT statements
+    Sql,
+    // GitHub-flavored Markdown pipe table
+    #[value(name = "md", alias = "markdown")]
+    Markdown,
+    // Self-contained HTML page with a single table
+    Html,
+    // Box-drawn aligned table and mysql `\G` style records, for reading in a terminal
+    Table,
+    Vertical,
 }
 
 impl OutputFormat {
+    /// Determines the format from the file extension using the format registry
+    ///
+    /// A trailing compression extension is skipped, so `data.csv.gz` is CSV.
     pub fn from_extension(path: &std::path::Path) -> Self {
-        match path.extension().and_then(|s| s.to_str()) {
-            Some("csv") => Self::Csv,
-            Some("json") => Self::Json,
-            _ => Self::Tsv, // Default fallback
-        }
+        crate::compression::strip_compression_extension(path)
+            .extension()
+            .and_then(|s| s.to_str())
+            .and_then(crate::formats::format_for_extension)
+            .unwrap_or(Self::Tsv) // Default fallback
     }
 
     pub fn as_str(&self) -> &'static str {
@@ -110,6 +314,16 @@ impl OutputFormat {
             Self::Csv => "csv",
             Self::Json => "json",
             Self::Tsv => "tsv",
+            Self::Ndjson => "ndjson",
+            Self::Parquet => "parquet",
+            Self::Arrow => "arrow",
+            Self::ArrowStream => "arrow-stream",
+            Self::Xlsx => "xlsx",
+            Self::Sql => "sql",
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Table => "table",
+            Self::Vertical => "vertical",
         }
     }
 }
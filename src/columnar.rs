@@ -0,0 +1,458 @@
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow, bail};
+use arrow_array::builder::{
+    ArrayBuilder, BinaryBuilder, Date32Builder, Decimal128Builder, Decimal256Builder, Float64Builder, Int64Builder,
+    StringBuilder, TimestampMicrosecondBuilder, UInt64Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch, RecordBatchOptions};
+use arrow_buffer::i256;
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use mysql::consts::ColumnFlags;
+use mysql::{Column, Value};
+
+use crate::mysql_value_to_string;
+use crate::temporal::{MICROS_PER_DAY, temporal_parts};
+use crate::types::ColumnKind;
+
+/// Largest precision representable by a 128-bit decimal
+const DECIMAL128_MAX_PRECISION: u32 = 38;
+
+/// Largest precision representable by a 256-bit decimal (MySQL allows at most 65)
+const DECIMAL256_MAX_PRECISION: u32 = 76;
+
+/// Largest scale MySQL allows for DECIMAL columns
+const MAX_DECIMAL_SCALE: u8 = 30;
+
+/// Number of rows buffered before they are handed to an encoder as one record batch
+pub(crate) const BATCH_ROWS: usize = 8 * 1024;
+
+/// Accumulates MySQL rows into typed Arrow record batches
+///
+/// The Arrow schema is derived from MySQL column metadata: integers become Int64/UInt64,
+/// FLOAT and DOUBLE become Float64, DECIMAL keeps its precision and scale, DATE becomes
+/// Date32, DATETIME and TIMESTAMP become microsecond timestamps, binary strings become
+/// Binary, and everything else (including TIME, which may exceed 24 hours) is Utf8.
+/// MySQL zero dates such as `0000-00-00` have no Arrow representation and become null.
+pub(crate) struct BatchBuilder {
+    schema: SchemaRef,
+    columns: Vec<ColumnBuilder>,
+    rows: usize,
+}
+
+impl BatchBuilder {
+    /// Creates a builder whose schema follows the MySQL column metadata
+    pub(crate) fn from_columns(columns: &[Column]) -> Result<Self> {
+        let names = crate::unique_column_names(&crate::column_names(columns));
+        let mut fields = Vec::with_capacity(columns.len());
+        let mut builders = Vec::with_capacity(columns.len());
+
+        for (column, name) in columns.iter().zip(names) {
+            let builder = ColumnBuilder::for_column(column)?;
+            fields.push(Field::new(name, builder.data_type(), true));
+            builders.push(builder);
+        }
+
+        Ok(Self::new(fields, builders))
+    }
+
+    /// Creates a builder with a Utf8 column per name, for output without column metadata
+    pub(crate) fn from_names(names: &[String]) -> Self {
+        let fields = crate::unique_column_names(names)
+            .into_iter()
+            .map(|name| Field::new(name, DataType::Utf8, true))
+            .collect();
+        let builders = names
+            .iter()
+            .map(|_| ColumnBuilder::Utf8(StringBuilder::new()))
+            .collect();
+
+        Self::new(fields, builders)
+    }
+
+    fn new(fields: Vec<Field>, columns: Vec<ColumnBuilder>) -> Self {
+        Self {
+            schema: Arc::new(Schema::new(fields)),
+            columns,
+            rows: 0,
+        }
+    }
+
+    /// Returns the Arrow schema of the batches produced by this builder
+    pub(crate) fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    /// Returns the number of rows buffered since the last batch was taken
+    pub(crate) fn len(&self) -> usize {
+        self.rows
+    }
+
+    /// Appends one row of MySQL values
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the row width does not match the schema or a value cannot be
+    /// converted to its column type.
+    pub(crate) fn append_values(&mut self, values: &[Value]) -> Result<()> {
+        if values.len() != self.columns.len() {
+            bail!("Row has {} values but the result set has {} columns", values.len(), self.columns.len());
+        }
+
+        for (index, (builder, value)) in self.columns.iter_mut().zip(values).enumerate() {
+            builder
+                .append(value)
+                .map_err(|e| anyhow!("{} in column '{}'", e, self.schema.field(index).name()))?;
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Appends one row of already-converted strings
+    pub(crate) fn append_strings(&mut self, row: &[String]) -> Result<()> {
+        let values: Vec<Value> = row
+            .iter()
+            .map(|field| Value::Bytes(field.as_bytes().to_vec()))
+            .collect();
+        self.append_values(&values)
+    }
+
+    /// Takes the buffered rows as a record batch, leaving the builder empty
+    pub(crate) fn take_batch(&mut self) -> Result<RecordBatch> {
+        let arrays: Vec<ArrayRef> = self.columns.iter_mut().map(ColumnBuilder::finish).collect();
+        let options = RecordBatchOptions::new().with_row_count(Some(self.rows));
+        self.rows = 0;
+
+        Ok(RecordBatch::try_new_with_options(self.schema(), arrays, &options)?)
+    }
+}
+
+/// Typed Arrow array builder for a single column
+enum ColumnBuilder {
+    Int64(Int64Builder),
+    UInt64(UInt64Builder),
+    Float64(Float64Builder),
+    Decimal128(Decimal128Builder, u8, i8),
+    Decimal256(Decimal256Builder, u8, i8),
+    Date32(Date32Builder),
+    Timestamp(TimestampMicrosecondBuilder),
+    Utf8(StringBuilder),
+    Binary(BinaryBuilder),
+}
+
+impl ColumnBuilder {
+    /// Picks the builder for a column from its MySQL metadata
+    fn for_column(column: &Column) -> Result<Self> {
+        Ok(match ColumnKind::from_column(column) {
+            ColumnKind::Integer => Self::Int64(Int64Builder::new()),
+            ColumnKind::UnsignedInteger => Self::UInt64(UInt64Builder::new()),
+            ColumnKind::Float => Self::Float64(Float64Builder::new()),
+            ColumnKind::Decimal => {
+                let (precision, scale) = decimal_precision_and_scale(column);
+                if u32::from(precision) <= DECIMAL128_MAX_PRECISION {
+                    let builder = Decimal128Builder::new().with_precision_and_scale(precision, scale)?;
+                    Self::Decimal128(builder, precision, scale)
+                } else {
+                    let builder = Decimal256Builder::new().with_precision_and_scale(precision, scale)?;
+                    Self::Decimal256(builder, precision, scale)
+                }
+            },
+            ColumnKind::Date => Self::Date32(Date32Builder::new()),
+            ColumnKind::DateTime => Self::Timestamp(TimestampMicrosecondBuilder::new()),
+            ColumnKind::Binary => Self::Binary(BinaryBuilder::new()),
+            ColumnKind::Time | ColumnKind::Text => Self::Utf8(StringBuilder::new()),
+        })
+    }
+
+    /// Returns the Arrow data type produced by this builder
+    fn data_type(&self) -> DataType {
+        match self {
+            Self::Int64(_) => DataType::Int64,
+            Self::UInt64(_) => DataType::UInt64,
+            Self::Float64(_) => DataType::Float64,
+            Self::Decimal128(_, precision, scale) => DataType::Decimal128(*precision, *scale),
+            Self::Decimal256(_, precision, scale) => DataType::Decimal256(*precision, *scale),
+            Self::Date32(_) => DataType::Date32,
+            Self::Timestamp(_) => DataType::Timestamp(TimeUnit::Microsecond, None),
+            Self::Utf8(_) => DataType::Utf8,
+            Self::Binary(_) => DataType::Binary,
+        }
+    }
+
+    /// Appends one value, converting it to the column's Arrow type
+    fn append(&mut self, value: &Value) -> Result<()> {
+        if let Value::NULL = value {
+            self.append_null();
+            return Ok(());
+        }
+
+        match self {
+            Self::Int64(builder) => {
+                let parsed = match value {
+                    Value::Int(i) => *i,
+                    Value::UInt(u) => i64::try_from(*u)
+                        .map_err(|_| anyhow!("Type conversion error: {} does not fit in a signed integer", u))?,
+                    _ => parse_text(value, "integer")?,
+                };
+                builder.append_value(parsed);
+            },
+            Self::UInt64(builder) => {
+                let parsed = match value {
+                    Value::UInt(u) => *u,
+                    Value::Int(i) => u64::try_from(*i)
+                        .map_err(|_| anyhow!("Type conversion error: {} does not fit in an unsigned integer", i))?,
+                    _ => parse_text(value, "unsigned integer")?,
+                };
+                builder.append_value(parsed);
+            },
+            Self::Float64(builder) => {
+                let parsed = match value {
+                    Value::Float(f) => f64::from(*f),
+                    Value::Double(d) => *d,
+                    _ => parse_text(value, "floating point number")?,
+                };
+                builder.append_value(parsed);
+            },
+            Self::Decimal128(builder, precision, scale) => {
+                let unscaled = unscaled_decimal(&mysql_value_to_string(value)?, *precision, *scale)?;
+                builder.append_value(
+                    unscaled
+                        .parse::<i128>()
+                        .map_err(|_| anyhow!("Type conversion error: invalid decimal '{}'", unscaled))?,
+                );
+            },
+            Self::Decimal256(builder, precision, scale) => {
+                let unscaled = unscaled_decimal(&mysql_value_to_string(value)?, *precision, *scale)?;
+                builder.append_value(
+                    i256::from_string(&unscaled)
+                        .ok_or_else(|| anyhow!("Type conversion error: invalid decimal '{}'", unscaled))?,
+                );
+            },
+            Self::Date32(builder) => match temporal_parts(value)? {
+                Some((days, _)) => builder.append_value(
+                    i32::try_from(days).map_err(|_| anyhow!("Type conversion error: date out of range"))?,
+                ),
+                None => builder.append_null(),
+            },
+            Self::Timestamp(builder) => match temporal_parts(value)? {
+                Some((days, micros)) => builder.append_value(days * MICROS_PER_DAY + micros),
+                None => builder.append_null(),
+            },
+            Self::Utf8(builder) => builder.append_value(mysql_value_to_string(value)?),
+            Self::Binary(builder) => match value {
+                Value::Bytes(bytes) => builder.append_value(bytes),
+                _ => builder.append_value(mysql_value_to_string(value)?),
+            },
+        }
+        Ok(())
+    }
+
+    fn append_null(&mut self) {
+        match self {
+            Self::Int64(builder) => builder.append_null(),
+            Self::UInt64(builder) => builder.append_null(),
+            Self::Float64(builder) => builder.append_null(),
+            Self::Decimal128(builder, ..) => builder.append_null(),
+            Self::Decimal256(builder, ..) => builder.append_null(),
+            Self::Date32(builder) => builder.append_null(),
+            Self::Timestamp(builder) => builder.append_null(),
+            Self::Utf8(builder) => builder.append_null(),
+            Self::Binary(builder) => builder.append_null(),
+        }
+    }
+
+    /// Returns the values appended so far as an array and resets the builder
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Int64(builder) => ArrayBuilder::finish(builder),
+            Self::UInt64(builder) => ArrayBuilder::finish(builder),
+            Self::Float64(builder) => ArrayBuilder::finish(builder),
+            Self::Decimal128(builder, ..) => ArrayBuilder::finish(builder),
+            Self::Decimal256(builder, ..) => ArrayBuilder::finish(builder),
+            Self::Date32(builder) => ArrayBuilder::finish(builder),
+            Self::Timestamp(builder) => ArrayBuilder::finish(builder),
+            Self::Utf8(builder) => ArrayBuilder::finish(builder),
+            Self::Binary(builder) => ArrayBuilder::finish(builder),
+        }
+    }
+}
+
+/// Derives DECIMAL precision and scale from the column's display length and decimals
+///
+/// MySQL reports the display length of `DECIMAL(M,D)` as M plus one for the decimal point
+/// (when D > 0) and one for the sign (unless UNSIGNED).
+fn decimal_precision_and_scale(column: &Column) -> (u8, i8) {
+    let scale = column.decimals().min(MAX_DECIMAL_SCALE);
+    let unsigned = column.flags().contains(ColumnFlags::UNSIGNED_FLAG);
+    let overhead = u32::from(scale > 0) + u32::from(!unsigned);
+    let precision = column
+        .column_length()
+        .saturating_sub(overhead)
+        .clamp(u32::from(scale).max(1), DECIMAL256_MAX_PRECISION);
+
+    // Both values are bounded by the clamps above
+    (precision as u8, scale as i8)
+}
+
+/// Converts decimal text such as `-12.50` into its unscaled integer digits (`-1250` at scale 2)
+fn unscaled_decimal(text: &str, precision: u8, scale: i8) -> Result<String> {
+    let invalid = || anyhow!("Type conversion error: invalid decimal '{}'", text);
+    let trimmed = text.trim();
+    let (negative, digits) = match trimmed.as_bytes().first() {
+        Some(b'-') => (true, &trimmed[1..]),
+        Some(b'+') => (false, &trimmed[1..]),
+        _ => (false, trimmed),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let scale = usize::try_from(scale).map_err(|_| invalid())?;
+
+    if integer.is_empty() && fraction.is_empty()
+        || !integer.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit())
+        || fraction.len() > scale
+    {
+        return Err(invalid());
+    }
+
+    let mut digits = String::with_capacity(integer.len() + scale);
+    digits.push_str(integer);
+    digits.push_str(fraction);
+    digits.extend(std::iter::repeat_n('0', scale - fraction.len()));
+    let significant = digits.trim_start_matches('0');
+    if significant.len() > usize::from(precision) {
+        bail!("Type conversion error: decimal '{}' exceeds precision {}", text, precision);
+    }
+
+    Ok(match (significant.is_empty(), negative) {
+        (true, _) => "0".to_string(),
+        (false, true) => format!("-{}", significant),
+        (false, false) => significant.to_string(),
+    })
+}
+
+/// Parses the text of a value as a number of the given type
+fn parse_text<T: std::str::FromStr>(value: &Value, type_name: &str) -> Result<T> {
+    let text = mysql_value_to_string(value)?;
+    text.trim()
+        .parse()
+        .map_err(|_| anyhow!("Type conversion error: cannot convert '{}' to {}", text, type_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Date32Type, Decimal128Type, Int64Type, TimestampMicrosecondType, UInt64Type};
+    use mysql::consts::ColumnType;
+
+    fn column(name: &str, column_type: ColumnType) -> Column {
+        Column::new(column_type)
+            .with_name(name.as_bytes())
+            .with_character_set(33)
+    }
+
+    fn text(value: &str) -> Value {
+        Value::Bytes(value.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_schema_follows_column_metadata() {
+        let columns = [
+            column("id", ColumnType::MYSQL_TYPE_LONGLONG),
+            column("count", ColumnType::MYSQL_TYPE_LONGLONG).with_flags(ColumnFlags::UNSIGNED_FLAG),
+            column("price", ColumnType::MYSQL_TYPE_NEWDECIMAL)
+                .with_column_length(12)
+                .with_decimals(2),
+            column("big", ColumnType::MYSQL_TYPE_NEWDECIMAL)
+                .with_column_length(66)
+                .with_decimals(0),
+            column("born", ColumnType::MYSQL_TYPE_DATE),
+            column("seen", ColumnType::MYSQL_TYPE_DATETIME),
+            column("payload", ColumnType::MYSQL_TYPE_BLOB).with_character_set(63),
+            column("id", ColumnType::MYSQL_TYPE_VAR_STRING),
+        ];
+        let builder = BatchBuilder::from_columns(&columns).unwrap();
+        let schema = builder.schema();
+
+        let types: Vec<&DataType> = schema.fields().iter().map(|field| field.data_type()).collect();
+        assert_eq!(
+            types,
+            vec![
+                &DataType::Int64,
+                &DataType::UInt64,
+                &DataType::Decimal128(10, 2),
+                &DataType::Decimal256(65, 0),
+                &DataType::Date32,
+                &DataType::Timestamp(TimeUnit::Microsecond, None),
+                &DataType::Binary,
+                &DataType::Utf8,
+            ]
+        );
+        assert_eq!(schema.field(7).name(), "id_2");
+    }
+
+    #[test]
+    fn test_values_are_typed() {
+        let columns = [
+            column("id", ColumnType::MYSQL_TYPE_LONG),
+            column("count", ColumnType::MYSQL_TYPE_LONGLONG).with_flags(ColumnFlags::UNSIGNED_FLAG),
+            column("price", ColumnType::MYSQL_TYPE_NEWDECIMAL)
+                .with_column_length(12)
+                .with_decimals(2),
+            column("born", ColumnType::MYSQL_TYPE_DATE),
+            column("seen", ColumnType::MYSQL_TYPE_TIMESTAMP),
+        ];
+        let mut builder = BatchBuilder::from_columns(&columns).unwrap();
+        builder
+            .append_values(&[
+                text("-7"),
+                text("18446744073709551615"),
+                text("-12.5"),
+                text("2024-02-29"),
+                text("2024-02-29 12:30:45.250000"),
+            ])
+            .unwrap();
+        builder
+            .append_values(&[
+                Value::Int(1),
+                Value::UInt(2),
+                Value::NULL,
+                text("0000-00-00"),
+                Value::NULL,
+            ])
+            .unwrap();
+        let batch = builder.take_batch().unwrap();
+
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(builder.len(), 0);
+        assert_eq!(batch.column(0).as_primitive::<Int64Type>().values(), &[-7, 1]);
+        assert_eq!(batch.column(1).as_primitive::<UInt64Type>().value(0), u64::MAX);
+        let prices = batch.column(2).as_primitive::<Decimal128Type>();
+        assert_eq!(prices.value(0), -1250);
+        assert!(prices.is_null(1));
+        let dates = batch.column(3).as_primitive::<Date32Type>();
+        assert_eq!(dates.value(0), 19782);
+        assert!(dates.is_null(1));
+        let timestamps = batch.column(4).as_primitive::<TimestampMicrosecondType>();
+        assert_eq!(timestamps.value(0), 1_709_209_845_250_000);
+    }
+
+    #[test]
+    fn test_conversion_errors_name_the_column() {
+        let mut builder = BatchBuilder::from_columns(&[column("id", ColumnType::MYSQL_TYPE_LONG)]).unwrap();
+        let err = builder.append_values(&[text("abc")]).unwrap_err();
+        assert!(err.to_string().contains("Type conversion error"));
+        assert!(err.to_string().contains("column 'id'"));
+    }
+
+    #[test]
+    fn test_unscaled_decimal() {
+        assert_eq!(unscaled_decimal("123.45", 5, 2).unwrap(), "12345");
+        assert_eq!(unscaled_decimal("-0.5", 5, 2).unwrap(), "-50");
+        assert_eq!(unscaled_decimal("0.00", 5, 2).unwrap(), "0");
+        assert_eq!(unscaled_decimal("7", 5, 2).unwrap(), "700");
+        assert!(unscaled_decimal("1234.5", 5, 2).is_err());
+        assert!(unscaled_decimal("1.234", 5, 2).is_err());
+        assert!(unscaled_decimal("1e5", 5, 2).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::cli::Compression;
+
+/// Returns the compression selected by a file extension (case-insensitive), if any
+pub fn compression_for_extension(extension: &str) -> Option<Compression> {
+    match extension.to_ascii_lowercase().as_str() {
+        "gz" | "gzip" => Some(Compression::Gzip),
+        "zst" | "zstd" => Some(Compression::Zstd),
+        _ => None,
+    }
+}
+
+/// Returns the path without a trailing compression extension
+///
+/// `data.csv.gz` becomes `data.csv`, so the format can be detected from what remains.
+/// Paths without a compression extension are returned unchanged.
+pub fn strip_compression_extension(path: &Path) -> PathBuf {
+    match Compression::from_extension(path) {
+        Compression::None => path.to_path_buf(),
+        _ => path.with_extension(""),
+    }
+}
+
+/// Checks that compression support is compiled in and that the level suits the algorithm
+///
+/// # Errors
+///
+/// Returns an error if the level is out of range for the algorithm, if a level is given
+/// without compression, or if support for the algorithm was not compiled in.
+pub fn check_compression(compression: Compression, level: Option<i32>) -> Result<()> {
+    match compression {
+        Compression::None => match level {
+            Some(level) => anyhow::bail!("Invalid compression level {}: output is not compressed", level),
+            None => Ok(()),
+        },
+        Compression::Gzip => gzip_level(level).map(drop),
+        Compression::Zstd => zstd_level(level).map(drop),
+    }
+}
+
+/// Output stream that optionally compresses everything written to it
+///
+/// Compressed streams end with a trailer, so [`CompressedWriter::finish`] must be called
+/// once all output has been written; dropping the writer without finishing it leaves a
+/// truncated archive.
+pub enum CompressedWriter<W: Write> {
+    /// Output written as-is
+    Plain(W),
+    /// gzip (RFC 1952) stream
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<W>),
+    /// Zstandard frame
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+impl<W: Write> CompressedWriter<W> {
+    /// Wraps a writer with the given compression and optional level
+    ///
+    /// # Errors
+    ///
+    /// Fails for the same reasons as [`check_compression`].
+    pub fn new(writer: W, compression: Compression, level: Option<i32>) -> Result<Self> {
+        match compression {
+            Compression::None => {
+                check_compression(compression, level)?;
+                Ok(Self::Plain(writer))
+            },
+            Compression::Gzip => gzip(writer, level),
+            Compression::Zstd => zstd(writer, level),
+        }
+    }
+
+    /// Writes any compression trailer and returns the underlying writer, flushed
+    pub fn finish(self) -> Result<W> {
+        let mut writer = match self {
+            Self::Plain(writer) => writer,
+            #[cfg(feature = "gzip")]
+            Self::Gzip(encoder) => encoder.finish()?,
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => encoder.finish()?,
+        };
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_level(level: Option<i32>) -> Result<flate2::Compression> {
+    match level {
+        None => Ok(flate2::Compression::default()),
+        Some(level @ 0..=9) => Ok(flate2::Compression::new(level as u32)),
+        Some(level) => anyhow::bail!("Invalid compression level {} for gzip: expected 0-9", level),
+    }
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_level(_level: Option<i32>) -> Result<()> {
+    anyhow::bail!("gzip support not compiled in (rebuild with --features gzip)")
+}
+
+#[cfg(feature = "gzip")]
+fn gzip<W: Write>(writer: W, level: Option<i32>) -> Result<CompressedWriter<W>> {
+    Ok(CompressedWriter::Gzip(flate2::write::GzEncoder::new(writer, gzip_level(level)?)))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip<W: Write>(_writer: W, _level: Option<i32>) -> Result<CompressedWriter<W>> {
+    anyhow::bail!("gzip support not compiled in (rebuild with --features gzip)")
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_level(level: Option<i32>) -> Result<i32> {
+    let range = zstd::compression_level_range();
+    match level {
+        // Level 0 selects the library default (3)
+        None => Ok(0),
+        Some(level) if range.contains(&level) => Ok(level),
+        Some(level) => {
+            anyhow::bail!("Invalid compression level {} for zstd: expected {}-{}", level, range.start(), range.end())
+        },
+    }
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_level(_level: Option<i32>) -> Result<()> {
+    anyhow::bail!("zstd support not compiled in (rebuild with --features zstd)")
+}
+
+#[cfg(feature = "zstd")]
+fn zstd<W: Write>(writer: W, level: Option<i32>) -> Result<CompressedWriter<W>> {
+    Ok(CompressedWriter::Zstd(zstd::Encoder::new(writer, zstd_level(level)?)?))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd<W: Write>(_writer: W, _level: Option<i32>) -> Result<CompressedWriter<W>> {
+    anyhow::bail!("zstd support not compiled in (rebuild with --features zstd)")
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(writer) => writer.write(buf),
+            #[cfg(feature = "gzip")]
+            Self::Gzip(encoder) => encoder.write(buf),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(writer) => writer.flush(),
+            #[cfg(feature = "gzip")]
+            Self::Gzip(encoder) => encoder.flush(),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_from_extension() {
+        assert_eq!(Compression::from_extension(Path::new("data.csv.gz")), Compression::Gzip);
+        assert_eq!(Compression::from_extension(Path::new("data.json.ZST")), Compression::Zstd);
+        assert_eq!(Compression::from_extension(Path::new("data.csv")), Compression::None);
+        assert_eq!(strip_compression_extension(Path::new("out/data.tsv.gz")), Path::new("out/data.tsv"));
+        assert_eq!(strip_compression_extension(Path::new("out/data.tsv")), Path::new("out/data.tsv"));
+    }
+
+    #[test]
+    fn test_plain_passthrough() {
+        let mut writer = CompressedWriter::new(Vec::new(), Compression::None, None).unwrap();
+        writer.write_all(b"id\n1\n").unwrap();
+        assert_eq!(writer.finish().unwrap(), b"id\n1\n");
+
+        assert!(CompressedWriter::new(Vec::new(), Compression::None, Some(3)).is_err());
+        assert!(check_compression(Compression::None, None).is_ok());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_round_trip() {
+        use std::io::Read;
+
+        let mut writer = CompressedWriter::new(Vec::new(), Compression::Gzip, Some(9)).unwrap();
+        writer.write_all(b"id,name\n1,Alice\n").unwrap();
+        let compressed = writer.finish().unwrap();
+        assert_eq!(&compressed[..2], &[0x1f, 0x8b]);
+
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "id,name\n1,Alice\n");
+
+        assert!(CompressedWriter::new(Vec::new(), Compression::Gzip, Some(10)).is_err());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_round_trip() {
+        let mut writer = CompressedWriter::new(Vec::new(), Compression::Zstd, Some(19)).unwrap();
+        writer.write_all(b"{\"id\":1}\n").unwrap();
+        let compressed = writer.finish().unwrap();
+
+        assert_eq!(zstd::decode_all(compressed.as_slice()).unwrap(), b"{\"id\":1}\n");
+        assert!(CompressedWriter::new(Vec::new(), Compression::Zstd, Some(99)).is_err());
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_gzip_not_compiled_in() {
+        let err = CompressedWriter::new(Vec::new(), Compression::Gzip, None).err();
+        assert!(err.unwrap().to_string().contains("not compiled in"));
+    }
+}
@@ -1,7 +1,119 @@
-use std::io::{BufWriter, Write};
+use std::io::Write;
 
+use anyhow::Result;
 use csv::{QuoteStyle, WriterBuilder};
 
+use mysql::Value;
+
+use crate::{FormatWriter, OUTPUT_BUFFER_CAPACITY, RestartableWriter, mysql_value_to_string};
+
+/// CSV writer that implements the FormatWriter trait
+///
+/// Produces RFC4180 output, quoting fields only when necessary. Tab-delimited output is the
+/// same format with a tab as the delimiter, see [`CsvWriter::tsv`].
+pub struct CsvWriter<W: Write> {
+    writer: csv::Writer<W>,
+    delimiter: u8,
+    null_marker: String,
+    /// Column names, repeated at the top of each output after a restart
+    header: Vec<String>,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Creates a new comma-delimited CsvWriter over the specified writer
+    pub fn new(writer: W) -> Self {
+        Self::with_delimiter(writer, b',')
+    }
+
+    /// Creates a new tab-delimited CsvWriter over the specified writer
+    pub fn tsv(writer: W) -> Self {
+        Self::with_delimiter(writer, b'\t')
+    }
+
+    fn with_delimiter(writer: W, delimiter: u8) -> Self {
+        Self {
+            writer: Self::csv_writer(writer, delimiter),
+            delimiter,
+            null_marker: String::new(),
+            header: Vec::new(),
+        }
+    }
+
+    fn csv_writer(writer: W, delimiter: u8) -> csv::Writer<W> {
+        WriterBuilder::new()
+            .delimiter(delimiter)
+            .quote_style(QuoteStyle::Necessary)
+            .buffer_capacity(OUTPUT_BUFFER_CAPACITY)
+            .from_writer(writer)
+    }
+
+    /// Sets the string written for SQL NULL values (empty by default)
+    pub fn with_null_marker(mut self, null_marker: impl Into<String>) -> Self {
+        self.null_marker = null_marker.into();
+        self
+    }
+
+    /// Writes a single record of any byte-like fields
+    pub fn write_record<I, T>(&mut self, record: I) -> Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        self.writer.write_record(record)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> FormatWriter for CsvWriter<W> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        self.header = columns.to_vec();
+        // An empty header would otherwise be written as a lone `""` record
+        if columns.is_empty() {
+            return Ok(());
+        }
+        self.write_record(columns)
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        self.write_record(row)
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        let mut row = Vec::with_capacity(values.len());
+        for value in values {
+            match value {
+                Value::NULL => row.push(self.null_marker.clone()),
+                _ => row.push(mysql_value_to_string(value)?),
+            }
+        }
+        self.write_record(&row)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<W: Write> RestartableWriter<W> for CsvWriter<W> {
+    fn restart(&mut self, writer: W) -> Result<W> {
+        let previous = std::mem::replace(&mut self.writer, Self::csv_writer(writer, self.delimiter));
+        let previous = previous.into_inner().map_err(|e| e.into_error())?;
+        let header = std::mem::take(&mut self.header);
+        self.write_header(&header)?;
+        Ok(previous)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn into_inner(self: Box<Self>) -> Result<W> {
+        Ok(self.writer.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
 /// Writes rows to a CSV output using the provided writer.
 ///
 /// # Arguments
@@ -18,17 +130,7 @@ where
     F: IntoIterator<Item = String>,
     W: Write,
 {
-    let buffered_output = BufWriter::with_capacity(64 * 1024, output); // 64KB buffer for better performance with large datasets
-    let mut wtr = WriterBuilder::new()
-        .quote_style(QuoteStyle::Necessary)
-        .from_writer(buffered_output);
-
-    for row in rows {
-        wtr.write_record(row)?;
-    }
-
-    wtr.flush()?; // Ensure all data is written
-    Ok(())
+    write_bytes(rows, output)
 }
 
 /// Writes rows to a CSV output using the provided writer with generic field types.
@@ -56,15 +158,13 @@ where
     T: AsRef<[u8]>,
     W: Write,
 {
-    let mut wtr = WriterBuilder::new()
-        .quote_style(QuoteStyle::Necessary)
-        .from_writer(output);
+    let mut writer = CsvWriter::new(output);
 
     for row in rows {
-        wtr.write_record(row)?;
+        writer.write_record(row)?;
     }
 
-    Ok(())
+    writer.finalize()
 }
 
 /// Writes rows to a CSV output using the provided writer with streaming support.
@@ -87,13 +187,70 @@ where
     T: AsRef<[u8]>,
     W: Write,
 {
-    let mut wtr = WriterBuilder::new()
-        .quote_style(QuoteStyle::Necessary)
-        .from_writer(output);
+    write_bytes(rows, output)
+}
 
-    for row in rows {
-        wtr.write_record(row)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_writer_format() {
+        let mut output = Vec::new();
+        let mut writer = CsvWriter::new(&mut output);
+
+        writer.write_header(&["id".to_string(), "note".to_string()]).unwrap();
+        writer
+            .write_row(&["1".to_string(), "hello, world".to_string()])
+            .unwrap();
+        writer.write_row(&["2".to_string(), "plain".to_string()]).unwrap();
+        writer.finalize().unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, "id,note\n1,\"hello, world\"\n2,plain\n");
     }
 
-    Ok(())
+    #[test]
+    fn test_csv_writer_empty_header() {
+        let mut output = Vec::new();
+        let mut writer = CsvWriter::new(&mut output);
+
+        writer.write_header(&[]).unwrap();
+        writer.finalize().unwrap();
+
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_csv_writer_null_marker() {
+        let values = [Value::NULL, Value::Bytes(Vec::new()), Value::Int(7)];
+
+        let mut output = Vec::new();
+        let mut writer = CsvWriter::new(&mut output);
+        writer.write_values(&values).unwrap();
+        writer.finalize().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), ",,7\n");
+
+        let mut output = Vec::new();
+        let mut writer = CsvWriter::new(&mut output).with_null_marker("\\N");
+        writer.write_values(&values).unwrap();
+        writer.finalize().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "\\N,,7\n");
+    }
+
+    #[test]
+    fn test_csv_writer_restart_repeats_header() {
+        let mut writer = CsvWriter::new(Vec::new());
+        writer.write_header(&["id".to_string(), "name".to_string()]).unwrap();
+        writer.write_row(&["1".to_string(), "Alice".to_string()]).unwrap();
+
+        let first = writer.restart(Vec::new()).unwrap();
+        writer.write_row(&["2".to_string(), "Bob".to_string()]).unwrap();
+        let second = writer.restart(Vec::new()).unwrap();
+        let third = writer.restart(Vec::new()).unwrap();
+
+        assert_eq!(String::from_utf8(first).unwrap(), "id,name\n1,Alice\n");
+        assert_eq!(String::from_utf8(second).unwrap(), "id,name\n2,Bob\n");
+        assert_eq!(String::from_utf8(third).unwrap(), "id,name\n");
+    }
 }
@@ -8,6 +8,7 @@ pub const EXIT_CONFIG_ERROR: i32 = 2;
 pub const EXIT_DB_AUTH_ERROR: i32 = 3;
 pub const EXIT_QUERY_ERROR: i32 = 4;
 pub const EXIT_IO_ERROR: i32 = 5;
+pub const EXIT_QUERY_TIMEOUT: i32 = 6;
 
 /// Maps an error to the appropriate exit code and exits the process
 ///
@@ -16,8 +17,11 @@ pub const EXIT_IO_ERROR: i32 = 5;
 /// * `error` - The error to map to an exit code
 /// * `context` - Optional context message to log before exiting
 ///
-/// This function never returns as it calls `process::exit`
+/// Any output file that has not been completed is removed first, so a failed run never
+/// leaves partial output behind. This function never returns as it calls `process::exit`
 pub fn exit_with_error(error: Error, context: Option<&str>) -> ! {
+    crate::output::remove_partial_files();
+
     let exit_code = map_error_to_exit_code(&error);
     let error_msg = error.to_string();
 
@@ -48,6 +52,11 @@ pub fn map_error_to_exit_code(error: &Error) -> i32 {
         return EXIT_NO_ROWS;
     }
 
+    // Checked before the generic query patterns, which "query timed out" would also match
+    if error_string.contains("query timed out") {
+        return EXIT_QUERY_TIMEOUT;
+    }
+
     if error_string.contains("missing")
         || error_string.contains("invalid")
             && !error_string.contains("invalid certificate format")
@@ -95,14 +104,28 @@ pub fn map_error_to_exit_code(error: &Error) -> i32 {
     EXIT_QUERY_ERROR
 }
 
+/// Returns true if the error was caused by writing to a closed pipe
+///
+/// This happens when stdout is piped into a reader that exits early, such as `head`, and
+/// is treated as a normal end of output rather than a failure.
+pub fn is_broken_pipe(error: &Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|io_err| io_err.kind() == std::io::ErrorKind::BrokenPipe)
+            || cause.to_string().to_lowercase().contains("broken pipe")
+    })
+}
+
 /// Exits with success code (0)
 ///
 /// # Arguments
 ///
-/// * `message` - Optional success message to print before exiting
+/// * `message` - Optional success message to print to stderr before exiting, so that it
+///   never mixes with results written to stdout
 pub fn exit_success(message: Option<&str>) -> ! {
     if let Some(msg) = message {
-        println!("{}", msg);
+        eprintln!("{}", msg);
     }
     process::exit(EXIT_SUCCESS);
 }
@@ -200,6 +223,15 @@ mod tests {
         assert_eq!(map_error_to_exit_code(&error), EXIT_QUERY_ERROR);
     }
 
+    #[test]
+    fn test_map_error_to_exit_code_query_timeout() {
+        let error = anyhow!("Query timed out after 30s (--query-timeout)");
+        assert_eq!(map_error_to_exit_code(&error), EXIT_QUERY_TIMEOUT);
+
+        let error = anyhow!("Query timed out - server time limit reached");
+        assert_eq!(map_error_to_exit_code(&error), EXIT_QUERY_TIMEOUT);
+    }
+
     #[test]
     fn test_map_error_to_exit_code_io() {
         let error = anyhow!("File not found");
@@ -235,5 +267,18 @@ mod tests {
         assert_eq!(EXIT_DB_AUTH_ERROR, 3);
         assert_eq!(EXIT_QUERY_ERROR, 4);
         assert_eq!(EXIT_IO_ERROR, 5);
+        assert_eq!(EXIT_QUERY_TIMEOUT, 6);
+    }
+
+    #[test]
+    fn test_is_broken_pipe() {
+        let error = Error::new(std::io::Error::from(std::io::ErrorKind::BrokenPipe)).context("Output writing failed");
+        assert!(is_broken_pipe(&error));
+
+        let error = anyhow!(csv::Error::from(std::io::Error::from(std::io::ErrorKind::BrokenPipe)));
+        assert!(is_broken_pipe(&error));
+
+        let error = anyhow!("Failed to create output file: Permission denied");
+        assert!(!is_broken_pipe(&error));
     }
 }
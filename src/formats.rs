@@ -0,0 +1,446 @@
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::cli::OutputFormat;
+use crate::{FormatWriter, RestartableWriter};
+
+/// Options shared by all format writers
+#[derive(Debug, Clone, Default)]
+pub struct WriterOptions {
+    /// Pretty-print output where the format supports it
+    pub pretty: bool,
+    /// String written for SQL NULL by text formats that have no native NULL
+    pub null_marker: String,
+    /// Rows per row group for columnar formats (format default when unset)
+    pub row_group_size: Option<usize>,
+    /// Target table for SQL INSERT output
+    pub sql_table: Option<String>,
+    /// Rows per INSERT statement in SQL output
+    pub sql_batch_size: Option<usize>,
+    /// Cell width limits for table output
+    pub width_limits: Vec<crate::table::WidthLimit>,
+}
+
+/// Output sink handed to format writers
+///
+/// Sinks must be `Send` because some encoders (such as Parquet) require it.
+pub type Output<'a> = Box<dyn Write + Send + 'a>;
+
+/// Constructor for a boxed format writer over an arbitrary output
+pub type WriterFactory = for<'a> fn(Output<'a>, &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>>;
+
+/// Registry entry describing an output format
+pub struct FormatSpec {
+    /// The format this entry describes
+    pub format: OutputFormat,
+    /// File extensions (without the leading dot) that select this format
+    pub extensions: &'static [&'static str],
+    /// Creates a writer for this format
+    pub create: WriterFactory,
+}
+
+/// All known output formats
+///
+/// Each format has exactly one entry describing the extensions that select it and how to
+/// construct its writer, so adding a format means adding a writer and one entry here.
+/// Formats whose cargo feature is disabled stay registered so that their extensions are
+/// still recognized, but creating a writer for them fails with a clear error.
+pub static REGISTRY: &[FormatSpec] = &[
+    FormatSpec {
+        format: OutputFormat::Csv,
+        extensions: &["csv"],
+        create: create_csv,
+    },
+    FormatSpec {
+        format: OutputFormat::Json,
+        extensions: &["json"],
+        create: create_json,
+    },
+    FormatSpec {
+        format: OutputFormat::Tsv,
+        extensions: &["tsv"],
+        create: create_tsv,
+    },
+    FormatSpec {
+        format: OutputFormat::Ndjson,
+        extensions: &["jsonl", "ndjson"],
+        create: create_ndjson,
+    },
+    FormatSpec {
+        format: OutputFormat::Parquet,
+        extensions: &["parquet"],
+        create: create_parquet,
+    },
+    FormatSpec {
+        format: OutputFormat::Arrow,
+        extensions: &["arrow", "feather"],
+        create: create_arrow,
+    },
+    FormatSpec {
+        format: OutputFormat::ArrowStream,
+        extensions: &["arrows"],
+        create: create_arrow_stream,
+    },
+    FormatSpec {
+        format: OutputFormat::Xlsx,
+        extensions: &["xlsx"],
+        create: create_xlsx,
+    },
+    FormatSpec {
+        format: OutputFormat::Sql,
+        extensions: &["sql"],
+        create: create_sql,
+    },
+    FormatSpec {
+        format: OutputFormat::Markdown,
+        extensions: &["md", "markdown"],
+        create: create_markdown,
+    },
+    FormatSpec {
+        format: OutputFormat::Html,
+        extensions: &["html", "htm"],
+        create: create_html,
+    },
+    FormatSpec {
+        format: OutputFormat::Table,
+        extensions: &[],
+        create: create_table,
+    },
+    FormatSpec {
+        format: OutputFormat::Vertical,
+        extensions: &[],
+        create: create_vertical,
+    },
+];
+
+/// Looks up the registry entry for a format
+pub fn lookup(format: OutputFormat) -> Option<&'static FormatSpec> {
+    REGISTRY.iter().find(|spec| spec.format == format)
+}
+
+/// Finds the format registered for a file extension (case-insensitive)
+pub fn format_for_extension(extension: &str) -> Option<OutputFormat> {
+    REGISTRY
+        .iter()
+        .find(|spec| spec.extensions.iter().any(|ext| ext.eq_ignore_ascii_case(extension)))
+        .map(|spec| spec.format)
+}
+
+/// Creates a writer for the given format over the provided output
+///
+/// # Errors
+///
+/// Returns an error if the format is not registered or its support was not compiled in.
+pub fn create_writer<'a>(
+    format: OutputFormat,
+    output: Output<'a>,
+    options: &WriterOptions,
+) -> Result<Box<dyn FormatWriter + 'a>> {
+    let spec = lookup(format).ok_or_else(|| anyhow::anyhow!("Unsupported output format: {}", format.as_str()))?;
+    (spec.create)(output, options)
+}
+
+/// Formats that can be split into parts, since their writers implement [`RestartableWriter`]
+pub const SPLITTABLE_FORMATS: &[OutputFormat] = &[OutputFormat::Csv, OutputFormat::Tsv, OutputFormat::Json];
+
+/// Creates a writer that can continue on a new sink, for formats in [`SPLITTABLE_FORMATS`]
+///
+/// # Errors
+///
+/// Returns an error if the format cannot be split or its support was not compiled in.
+pub fn create_restartable_writer<'a, W: Write + 'a>(
+    format: OutputFormat,
+    output: W,
+    options: &WriterOptions,
+) -> Result<Box<dyn RestartableWriter<W> + 'a>> {
+    match format {
+        #[cfg(feature = "csv")]
+        OutputFormat::Csv => {
+            Ok(Box::new(crate::csv::CsvWriter::new(output).with_null_marker(options.null_marker.as_str())))
+        },
+        #[cfg(not(feature = "csv"))]
+        OutputFormat::Csv => anyhow::bail!("CSV support not compiled in"),
+        OutputFormat::Tsv => {
+            Ok(Box::new(crate::csv::CsvWriter::tsv(output).with_null_marker(options.null_marker.as_str())))
+        },
+        #[cfg(feature = "json")]
+        OutputFormat::Json => Ok(Box::new(crate::json::JsonWriter::new(output, options.pretty))),
+        #[cfg(not(feature = "json"))]
+        OutputFormat::Json => anyhow::bail!("JSON support not compiled in"),
+        _ => anyhow::bail!(
+            "Invalid output format for splitting: {} output cannot be split into parts (supported: csv, tsv, json)",
+            format.as_str()
+        ),
+    }
+}
+
+/// Creates a writer that holds several result sets in one document
+///
+/// Each call to [`FormatWriter::write_columns`] starts a new result set. Only JSON supports
+/// this, with one named array per result set.
+///
+/// # Errors
+///
+/// Returns an error if the format cannot hold several result sets or its support was not
+/// compiled in.
+pub fn create_result_sets_writer<'a>(
+    format: OutputFormat,
+    output: Output<'a>,
+    options: &WriterOptions,
+) -> Result<Box<dyn FormatWriter + 'a>> {
+    match format {
+        OutputFormat::Json => create_json_result_sets(output, options),
+        _ => anyhow::bail!(
+            "Invalid --result-sets combined: {} output cannot hold several result sets (supported: json)",
+            format.as_str()
+        ),
+    }
+}
+
+#[cfg(feature = "csv")]
+fn create_csv<'a>(output: Output<'a>, options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    Ok(Box::new(crate::csv::CsvWriter::new(output).with_null_marker(options.null_marker.as_str())))
+}
+
+#[cfg(not(feature = "csv"))]
+fn create_csv<'a>(_output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    anyhow::bail!("CSV support not compiled in")
+}
+
+fn create_tsv<'a>(output: Output<'a>, options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    Ok(Box::new(crate::csv::CsvWriter::tsv(output).with_null_marker(options.null_marker.as_str())))
+}
+
+#[cfg(feature = "json")]
+fn create_json<'a>(output: Output<'a>, options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    Ok(Box::new(crate::json::JsonWriter::new(output, options.pretty)))
+}
+
+#[cfg(not(feature = "json"))]
+fn create_json<'a>(_output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    anyhow::bail!("JSON support not compiled in")
+}
+
+#[cfg(feature = "json")]
+fn create_json_result_sets<'a>(output: Output<'a>, options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    Ok(Box::new(crate::json::JsonWriter::with_result_sets(output, options.pretty)))
+}
+
+#[cfg(not(feature = "json"))]
+fn create_json_result_sets<'a>(_output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    anyhow::bail!("JSON support not compiled in")
+}
+
+#[cfg(feature = "json")]
+fn create_ndjson<'a>(output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    Ok(Box::new(crate::ndjson::NdjsonWriter::new(output)))
+}
+
+#[cfg(not(feature = "json"))]
+fn create_ndjson<'a>(_output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    anyhow::bail!("NDJSON support not compiled in (requires the json feature)")
+}
+
+#[cfg(feature = "parquet")]
+fn create_parquet<'a>(output: Output<'a>, options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    let row_group_size = options.row_group_size.unwrap_or(crate::parquet::DEFAULT_ROW_GROUP_SIZE);
+    Ok(Box::new(crate::parquet::ParquetWriter::new(output).with_row_group_size(row_group_size)))
+}
+
+#[cfg(not(feature = "parquet"))]
+fn create_parquet<'a>(_output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    anyhow::bail!("Parquet support not compiled in (rebuild with --features parquet)")
+}
+
+#[cfg(feature = "arrow")]
+fn create_arrow<'a>(output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    Ok(Box::new(crate::arrow::ArrowIpcWriter::file(output)))
+}
+
+#[cfg(not(feature = "arrow"))]
+fn create_arrow<'a>(_output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    anyhow::bail!("Arrow support not compiled in (rebuild with --features arrow)")
+}
+
+#[cfg(feature = "arrow")]
+fn create_arrow_stream<'a>(output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    Ok(Box::new(crate::arrow::ArrowIpcWriter::stream(output)))
+}
+
+#[cfg(not(feature = "arrow"))]
+fn create_arrow_stream<'a>(_output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    anyhow::bail!("Arrow support not compiled in (rebuild with --features arrow)")
+}
+
+#[cfg(feature = "xlsx")]
+fn create_xlsx<'a>(output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    Ok(Box::new(crate::xlsx::XlsxWriter::new(output)))
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn create_xlsx<'a>(_output: Output<'a>, _options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    anyhow::bail!("XLSX support not compiled in (rebuild with --features xlsx)")
+}
+
+fn create_sql<'a>(output: Output<'a>, options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    let table = options.sql_table.as_deref().unwrap_or(crate::sql::DEFAULT_TABLE);
+    let rows = options.sql_batch_size.unwrap_or(crate::sql::DEFAULT_ROWS_PER_STATEMENT);
+    Ok(Box::new(
+        crate::sql::SqlWriter::new(output)
+            .with_table(table)
+            .with_rows_per_statement(rows),
+    ))
+}
+
+fn create_markdown<'a>(output: Output<'a>, options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    Ok(Box::new(crate::markdown::MarkdownWriter::new(output).with_null_marker(options.null_marker.as_str())))
+}
+
+fn create_html<'a>(output: Output<'a>, options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    Ok(Box::new(crate::html::HtmlWriter::new(output).with_null_marker(options.null_marker.as_str())))
+}
+
+fn create_table<'a>(output: Output<'a>, options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    let mut writer = crate::table::TableWriter::new(output).with_width_limits(options.width_limits.iter().cloned());
+    if !options.null_marker.is_empty() {
+        writer = writer.with_null_marker(options.null_marker.as_str());
+    }
+    Ok(Box::new(writer))
+}
+
+fn create_vertical<'a>(output: Output<'a>, options: &WriterOptions) -> Result<Box<dyn FormatWriter + 'a>> {
+    let mut writer = crate::vertical::VerticalWriter::new(output);
+    if !options.null_marker.is_empty() {
+        writer = writer.with_null_marker(options.null_marker.as_str());
+    }
+    Ok(Box::new(writer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    /// Writes a header and one row through the registry and returns the raw output
+    fn render_bytes(format: OutputFormat) -> Vec<u8> {
+        let mut output = Vec::new();
+        {
+            let mut writer = create_writer(format, Box::new(&mut output), &WriterOptions::default()).unwrap();
+            writer.write_header(&["id".to_string(), "name".to_string()]).unwrap();
+            writer.write_row(&["1".to_string(), "Alice".to_string()]).unwrap();
+            writer.finalize().unwrap();
+        }
+        output
+    }
+
+    /// Writes a header and one row through the registry and returns the output as text
+    fn render(format: OutputFormat) -> String {
+        String::from_utf8(render_bytes(format)).unwrap()
+    }
+
+    #[test]
+    fn test_every_format_is_registered_once() {
+        for format in OutputFormat::value_variants() {
+            assert_eq!(REGISTRY.iter().filter(|spec| spec.format == *format).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_format_for_extension() {
+        assert_eq!(format_for_extension("csv"), Some(OutputFormat::Csv));
+        assert_eq!(format_for_extension("JSON"), Some(OutputFormat::Json));
+        assert_eq!(format_for_extension("tsv"), Some(OutputFormat::Tsv));
+        assert_eq!(format_for_extension("jsonl"), Some(OutputFormat::Ndjson));
+        assert_eq!(format_for_extension("ndjson"), Some(OutputFormat::Ndjson));
+        assert_eq!(format_for_extension("parquet"), Some(OutputFormat::Parquet));
+        assert_eq!(format_for_extension("feather"), Some(OutputFormat::Arrow));
+        assert_eq!(format_for_extension("arrows"), Some(OutputFormat::ArrowStream));
+        assert_eq!(format_for_extension("XLSX"), Some(OutputFormat::Xlsx));
+        assert_eq!(format_for_extension("sql"), Some(OutputFormat::Sql));
+        assert_eq!(format_for_extension("md"), Some(OutputFormat::Markdown));
+        assert_eq!(format_for_extension("markdown"), Some(OutputFormat::Markdown));
+        assert_eq!(format_for_extension("html"), Some(OutputFormat::Html));
+        assert_eq!(format_for_extension("HTM"), Some(OutputFormat::Html));
+        assert_eq!(format_for_extension("txt"), None);
+    }
+
+    #[test]
+    fn test_create_writer_tsv() {
+        assert_eq!(render(OutputFormat::Tsv), "id\tname\n1\tAlice\n");
+    }
+
+    #[test]
+    fn test_create_writer_sql() {
+        assert_eq!(render(OutputFormat::Sql), "INSERT INTO `export` (`id`, `name`) VALUES\n('1', 'Alice');\n");
+    }
+
+    #[test]
+    fn test_create_writer_markdown() {
+        assert_eq!(render(OutputFormat::Markdown), "| id | name |\n| --- | --- |\n| 1 | Alice |\n");
+    }
+
+    #[test]
+    fn test_create_writer_html() {
+        assert!(render(OutputFormat::Html).contains("<tr><td>1</td><td>Alice</td></tr>"));
+    }
+
+    #[test]
+    fn test_create_writer_table() {
+        assert_eq!(
+            render(OutputFormat::Table),
+            "┌────┬───────┐\n│ id │ name  │\n├────┼───────┤\n│ 1  │ Alice │\n└────┴───────┘\n"
+        );
+    }
+
+    #[test]
+    fn test_create_writer_vertical() {
+        assert!(
+            render(OutputFormat::Vertical).ends_with(" 1. row ***************************\n  id: 1\nname: Alice\n")
+        );
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_create_writer_csv() {
+        assert_eq!(render(OutputFormat::Csv), "id,name\n1,Alice\n");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_create_writer_json() {
+        assert_eq!(render(OutputFormat::Json), r#"{"data":[{"id":"1","name":"Alice"}]}"#);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_create_writer_ndjson() {
+        assert_eq!(render(OutputFormat::Ndjson), "{\"id\":\"1\",\"name\":\"Alice\"}\n");
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_create_writer_parquet() {
+        assert!(render_bytes(OutputFormat::Parquet).starts_with(b"PAR1"));
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_create_writer_arrow() {
+        assert!(render_bytes(OutputFormat::Arrow).starts_with(b"ARROW1"));
+        assert!(!render_bytes(OutputFormat::ArrowStream).starts_with(b"ARROW1"));
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn test_create_writer_xlsx() {
+        assert!(render_bytes(OutputFormat::Xlsx).starts_with(b"PK"));
+    }
+
+    #[cfg(not(feature = "parquet"))]
+    #[test]
+    fn test_create_writer_parquet_not_compiled_in() {
+        let err = create_writer(OutputFormat::Parquet, Box::new(Vec::new()), &WriterOptions::default()).err();
+        assert!(err.unwrap().to_string().contains("not compiled in"));
+    }
+}
@@ -0,0 +1,202 @@
+use std::io::{BufWriter, Write};
+
+use anyhow::Result;
+use mysql::{Column, Value};
+
+use crate::types::column_kinds;
+use crate::{FormatWriter, OUTPUT_BUFFER_CAPACITY, mysql_value_to_string};
+
+/// Document head written before the table; the page has no external resources
+const PAGE_START: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Query results</title>
+<style>
+table { border-collapse: collapse; font-family: sans-serif; font-size: 14px; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; white-space: pre-wrap; }
+th { background: #f0f0f0; }
+td.num { text-align: right; }
+td.null { color: #999; font-style: italic; }
+</style>
+</head>
+<body>
+<table>
+";
+
+/// Closes the table and the document
+const PAGE_END: &str = "</tbody>
+</table>
+</body>
+</html>
+";
+
+/// HTML table writer that implements the FormatWriter trait
+///
+/// Writes a self-contained HTML page holding a single `<table>`, with the column names in
+/// `<thead>` and one `<tr>` per row in `<tbody>`. All text is entity-escaped, line breaks
+/// inside values are preserved by the embedded stylesheet, and NULL cells carry the `null`
+/// class so they can be told apart from empty strings.
+pub struct HtmlWriter<W: Write> {
+    writer: BufWriter<W>,
+    null_marker: String,
+    numeric: Vec<bool>,
+    started: bool,
+}
+
+impl<W: Write> HtmlWriter<W> {
+    /// Creates a new HtmlWriter over the specified writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::with_capacity(OUTPUT_BUFFER_CAPACITY, writer),
+            null_marker: String::new(),
+            numeric: Vec::new(),
+            started: false,
+        }
+    }
+
+    /// Sets the text shown in NULL cells (empty by default)
+    pub fn with_null_marker(mut self, null_marker: impl Into<String>) -> Self {
+        self.null_marker = null_marker.into();
+        self
+    }
+
+    fn start(&mut self) -> Result<()> {
+        if !self.started {
+            self.writer.write_all(PAGE_START.as_bytes())?;
+            self.started = true;
+        }
+        Ok(())
+    }
+
+    /// Writes one `<tr>` from `(text, class)` cells
+    fn write_cells<'a>(&mut self, cells: impl IntoIterator<Item = (&'a str, Option<&'static str>)>) -> Result<()> {
+        let mut line = String::from("<tr>");
+        for (text, class) in cells {
+            match class {
+                Some(class) => {
+                    line.push_str("<td class=\"");
+                    line.push_str(class);
+                    line.push_str("\">");
+                },
+                None => line.push_str("<td>"),
+            }
+            push_escaped(&mut line, text);
+            line.push_str("</td>");
+        }
+        line.push_str("</tr>");
+        writeln!(self.writer, "{}", line)?;
+        Ok(())
+    }
+
+    fn class(&self, index: usize) -> Option<&'static str> {
+        self.numeric.get(index).copied().unwrap_or(false).then_some("num")
+    }
+}
+
+impl<W: Write> FormatWriter for HtmlWriter<W> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        self.start()?;
+        let mut line = String::from("<thead>\n<tr>");
+        for column in columns {
+            line.push_str("<th>");
+            push_escaped(&mut line, column);
+            line.push_str("</th>");
+        }
+        line.push_str("</tr>\n</thead>\n<tbody>");
+        writeln!(self.writer, "{}", line)?;
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        self.write_cells(row.iter().map(|field| (field.as_str(), None)))
+    }
+
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        self.numeric = column_kinds(columns)
+            .into_iter()
+            .map(|kind| kind.is_numeric())
+            .collect();
+        self.write_header(&crate::column_names(columns))
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        let mut cells = Vec::with_capacity(values.len());
+        for (index, value) in values.iter().enumerate() {
+            match value {
+                Value::NULL => cells.push((self.null_marker.clone(), Some("null"))),
+                _ => cells.push((mysql_value_to_string(value)?, self.class(index))),
+            }
+        }
+        self.write_cells(cells.iter().map(|(text, class)| (text.as_str(), *class)))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if !self.started {
+            self.write_header(&[])?;
+        }
+        self.writer.write_all(PAGE_END.as_bytes())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Appends text with the HTML special characters replaced by entities
+fn push_escaped(out: &mut String, text: &str) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mysql::consts::ColumnType;
+
+    #[test]
+    fn test_html_table() {
+        let columns = [
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+                .with_name(b"<name>")
+                .with_character_set(33),
+        ];
+
+        let mut output = Vec::new();
+        let mut writer = HtmlWriter::new(&mut output);
+        writer.write_columns(&columns).unwrap();
+        writer
+            .write_values(&[Value::Int(1), Value::Bytes(b"Tom & \"Jerry\" <'x'>".to_vec())])
+            .unwrap();
+        writer.write_values(&[Value::Int(2), Value::NULL]).unwrap();
+        writer.finalize().unwrap();
+
+        let html = String::from_utf8(output).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<thead>\n<tr><th>id</th><th>&lt;name&gt;</th></tr>\n</thead>\n<tbody>\n"));
+        assert!(
+            html.contains(
+                "<tr><td class=\"num\">1</td><td>Tom &amp; &quot;Jerry&quot; &lt;&#39;x&#39;&gt;</td></tr>\n"
+            )
+        );
+        assert!(html.contains("<tr><td class=\"num\">2</td><td class=\"null\"></td></tr>\n"));
+        assert!(html.ends_with("</tbody>\n</table>\n</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn test_html_without_header_is_complete_page() {
+        let mut output = Vec::new();
+        let writer = HtmlWriter::new(&mut output);
+        writer.finalize().unwrap();
+
+        let html = String::from_utf8(output).unwrap();
+        assert!(html.contains("<thead>\n<tr></tr>\n</thead>\n<tbody>\n</tbody>"));
+    }
+}
@@ -1,86 +1,257 @@
-use std::{
-    collections::BTreeMap,
-    io::{BufWriter, Write},
-};
+use std::io::{BufWriter, Write};
 
-use crate::FormatWriter;
+use crate::types::{ColumnKind, column_kinds};
+use crate::{FormatWriter, OUTPUT_BUFFER_CAPACITY, RestartableWriter, mysql_value_to_string};
 use anyhow::Result;
+use mysql::{Column, Value};
 
 /// JSON writer that implements the FormatWriter trait
+///
+/// When column metadata is provided through [`FormatWriter::write_columns`], each value is
+/// encoded according to its column type: integer, floating point and DECIMAL columns become
+/// JSON numbers (DECIMAL digits are written verbatim so no precision is lost), and every other
+/// column stays a JSON string. SQL NULL is always written as `null`. Without metadata all
+/// values are written as strings.
+///
+/// Object keys follow the column order returned by the database. Duplicate column names,
+/// such as two `id` columns from a join, are disambiguated with a numeric suffix (`id`, `id_2`)
+/// so no value is ever overwritten.
 pub struct JsonWriter<W: Write> {
     writer: BufWriter<W>,
-    columns: Vec<String>,
+    encoder: ObjectEncoder,
     first_row: bool,
     pretty: bool,
+    /// Result sets started so far, when each one is written as a named array
+    result_sets: Option<usize>,
 }
 
 impl<W: Write> JsonWriter<W> {
     /// Creates a new JsonWriter with the specified writer and pretty printing option
     pub fn new(writer: W, pretty: bool) -> Self {
         Self {
-            writer: BufWriter::with_capacity(64 * 1024, writer), // 64KB buffer for better performance
-            columns: Vec::new(),
+            writer: BufWriter::with_capacity(OUTPUT_BUFFER_CAPACITY, writer),
+            encoder: ObjectEncoder::default(),
             first_row: true,
             pretty,
+            result_sets: None,
         }
     }
+
+    /// Creates a JsonWriter that writes several result sets into one document
+    ///
+    /// Each call to [`FormatWriter::write_header`] starts a new array, named `result_1`,
+    /// `result_2` and so on, and [`FormatWriter::finish`] closes the document:
+    /// `{"result_1":[...],"result_2":[...]}`.
+    pub fn with_result_sets(writer: W, pretty: bool) -> Self {
+        Self {
+            result_sets: Some(0),
+            ..Self::new(writer, pretty)
+        }
+    }
+
+    /// Writes one JSON object from already-encoded values
+    fn write_object(&mut self, encoded: Vec<String>) -> Result<()> {
+        if !self.first_row {
+            write!(self.writer, ",")?;
+        }
+        self.first_row = false;
+        self.encoder.write_object(&mut self.writer, encoded, self.pretty)
+    }
 }
 
 impl<W: Write> FormatWriter for JsonWriter<W> {
     fn write_header(&mut self, columns: &[String]) -> Result<()> {
-        self.columns = columns.to_vec();
+        self.encoder.set_keys(columns);
         self.first_row = true;
-        write!(self.writer, "{{\"data\":[")?;
+        match self.result_sets.as_mut() {
+            Some(count) => {
+                let opening = if *count == 0 { "{" } else { "]," };
+                *count += 1;
+                write!(self.writer, "{}\"result_{}\":[", opening, count)?;
+            },
+            None => write!(self.writer, "{{\"data\":[")?,
+        }
         Ok(())
     }
 
     fn write_row(&mut self, row: &[String]) -> Result<()> {
-        if !self.first_row {
-            write!(self.writer, ",")?;
-        }
-        self.first_row = false;
+        let encoded = self.encoder.encode_strings(row);
+        self.write_object(encoded)
+    }
 
-        // Create ordered map for deterministic output
-        let mut obj = BTreeMap::new();
-        for (col, val) in self.columns.iter().zip(row.iter()) {
-            // Convert string values to appropriate JSON types when possible
-            let json_value = if val.is_empty() {
-                // Preserve empty strings as strings, not null
-                serde_json::Value::String(val.clone())
-            } else if let Ok(num) = val.parse::<u64>() {
-                // Try u64 first to preserve large unsigned values
-                serde_json::Value::Number(num.into())
-            } else if let Ok(num) = val.parse::<i64>() {
-                // Then try i64 for signed integers
-                serde_json::Value::Number(num.into())
-            } else if let Ok(num) = val.parse::<f64>() {
-                // Parse f64 and validate with from_f64
-                serde_json::Number::from_f64(num)
-                    .map(serde_json::Value::Number)
-                    .unwrap_or_else(|| serde_json::Value::String(val.clone()))
-            } else if val.to_lowercase() == "true" || val.to_lowercase() == "false" {
-                // Case-insensitive boolean detection
-                serde_json::Value::Bool(val.to_lowercase() == "true")
-            } else {
-                serde_json::Value::String(val.clone())
-            };
-            obj.insert(col.clone(), json_value);
-        }
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        self.encoder.set_kinds(columns);
+        self.write_header(&crate::column_names(columns))
+    }
 
-        if self.pretty {
-            serde_json::to_writer_pretty(&mut self.writer, &obj)?;
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        let encoded = self.encoder.encode_values(values)?;
+        self.write_object(encoded)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if self.result_sets == Some(0) {
+            write!(self.writer, "{{}}")?;
         } else {
-            serde_json::to_writer(&mut self.writer, &obj)?;
+            write!(self.writer, "]}}")?;
         }
-
+        self.writer.flush()?;
         Ok(())
     }
+}
 
-    fn finalize(mut self) -> Result<()> {
+impl<W: Write> RestartableWriter<W> for JsonWriter<W> {
+    fn restart(&mut self, writer: W) -> Result<W> {
         write!(self.writer, "]}}")?;
+        let previous = std::mem::replace(&mut self.writer, BufWriter::with_capacity(OUTPUT_BUFFER_CAPACITY, writer));
+        let previous = previous.into_inner().map_err(|e| e.into_error())?;
+        self.first_row = true;
+        write!(self.writer, "{{\"data\":[")?;
+        Ok(previous)
+    }
+
+    fn flush(&mut self) -> Result<()> {
         self.writer.flush()?;
         Ok(())
     }
+
+    fn into_inner(mut self: Box<Self>) -> Result<W> {
+        write!(self.writer, "]}}")?;
+        Ok(self.writer.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
+/// Encodes rows as JSON objects using column names and kinds
+///
+/// Shared by the JSON and NDJSON writers so both formats convert values identically.
+#[derive(Debug, Default)]
+pub(crate) struct ObjectEncoder {
+    /// Object keys in column order, already encoded as JSON strings
+    keys: Vec<String>,
+    kinds: Vec<ColumnKind>,
+}
+
+impl ObjectEncoder {
+    /// Sets the object keys from column names, disambiguating duplicates
+    pub(crate) fn set_keys(&mut self, columns: &[String]) {
+        self.keys = crate::unique_column_names(columns)
+            .iter()
+            .map(|key| encode_string(key))
+            .collect();
+    }
+
+    /// Sets the column kinds used to pick each value's JSON type
+    pub(crate) fn set_kinds(&mut self, columns: &[Column]) {
+        self.kinds = column_kinds(columns);
+    }
+
+    /// Returns the kind of the column at `index`, treating unknown columns as text
+    fn kind(&self, index: usize) -> ColumnKind {
+        self.kinds.get(index).copied().unwrap_or(ColumnKind::Text)
+    }
+
+    /// Encodes a row of strings according to the column kinds
+    pub(crate) fn encode_strings(&self, row: &[String]) -> Vec<String> {
+        row.iter()
+            .enumerate()
+            .map(|(index, val)| encode_text(self.kind(index), val))
+            .collect()
+    }
+
+    /// Encodes a row of MySQL values according to the column kinds
+    pub(crate) fn encode_values(&self, values: &[Value]) -> Result<Vec<String>> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| encode_value(self.kind(index), value))
+            .collect()
+    }
+
+    /// Writes one JSON object from already-encoded values
+    pub(crate) fn write_object<W: Write>(&self, writer: &mut W, encoded: Vec<String>, pretty: bool) -> Result<()> {
+        // Keys are written in column order for deterministic output
+        let separator = if pretty { ": " } else { ":" };
+        let mut fields = 0;
+        write!(writer, "{{")?;
+        for (key, val) in self.keys.iter().zip(encoded) {
+            if fields > 0 {
+                write!(writer, ",")?;
+            }
+            if pretty {
+                write!(writer, "\n  ")?;
+            }
+            write!(writer, "{}{}{}", key, separator, val)?;
+            fields += 1;
+        }
+        if pretty && fields > 0 {
+            writeln!(writer)?;
+        }
+        write!(writer, "}}")?;
+
+        Ok(())
+    }
+}
+
+/// Encodes a MySQL value as JSON text according to its column kind
+fn encode_value(kind: ColumnKind, value: &Value) -> Result<String> {
+    match value {
+        Value::NULL => Ok("null".to_string()),
+        Value::Int(i) if kind.is_numeric() => Ok(i.to_string()),
+        Value::UInt(u) if kind.is_numeric() => Ok(u.to_string()),
+        // Shortest f32 representation avoids widening artifacts such as 3.140000104904175
+        Value::Float(f) if kind.is_numeric() => Ok(encode_text(ColumnKind::Float, &f.to_string())),
+        Value::Double(d) if kind.is_numeric() => Ok(encode_text(ColumnKind::Float, &d.to_string())),
+        Value::Bytes(bytes) if kind.is_numeric() => match std::str::from_utf8(bytes) {
+            Ok(text) => Ok(encode_text(kind, text)),
+            Err(_) => Ok(encode_string(&mysql_value_to_string(value)?)),
+        },
+        _ => Ok(encode_string(&mysql_value_to_string(value)?)),
+    }
+}
+
+/// Encodes the text form of a value as JSON according to its column kind
+///
+/// Numeric text that does not parse for its kind falls back to a JSON string.
+fn encode_text(kind: ColumnKind, text: &str) -> String {
+    match kind {
+        ColumnKind::Integer => match text.parse::<i64>() {
+            Ok(num) => num.to_string(),
+            Err(_) => encode_string(text),
+        },
+        ColumnKind::UnsignedInteger => match text.parse::<u64>() {
+            Ok(num) => num.to_string(),
+            Err(_) => encode_string(text),
+        },
+        ColumnKind::Float => match text.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+            Some(num) => num.to_string(),
+            None => encode_string(text),
+        },
+        ColumnKind::Decimal if is_json_number(text) => text.to_string(),
+        _ => encode_string(text),
+    }
+}
+
+/// Encodes a string as a JSON string literal
+fn encode_string(text: &str) -> String {
+    serde_json::Value::String(text.to_string()).to_string()
+}
+
+/// Returns true if `text` is a valid JSON number literal without an exponent
+///
+/// MySQL renders DECIMAL values in this form, so they can be emitted verbatim.
+fn is_json_number(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let (int_part, frac_part) = match digits.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (digits, None),
+    };
+
+    let int_valid = !int_part.is_empty()
+        && int_part.bytes().all(|b| b.is_ascii_digit())
+        && (int_part == "0" || !int_part.starts_with('0'));
+    let frac_valid = frac_part.is_none_or(|frac| !frac.is_empty() && frac.bytes().all(|b| b.is_ascii_digit()));
+
+    int_valid && frac_valid
 }
 
 /// Writes rows to a JSON output using the provided writer.
@@ -146,6 +317,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mysql::consts::{ColumnFlags, ColumnType};
     use std::io::Cursor;
 
     #[test]
@@ -172,110 +344,250 @@ mod tests {
     }
 
     #[test]
-    fn test_json_type_inference() {
-        let mut cursor = Cursor::new(Vec::new());
-        let mut writer = JsonWriter::new(&mut cursor, false);
-
+    fn test_json_writer_restart_writes_standalone_documents() {
+        let columns = [
+            column("id", ColumnType::MYSQL_TYPE_LONG, ColumnFlags::empty()),
+            column("name", ColumnType::MYSQL_TYPE_VAR_STRING, ColumnFlags::empty()),
+        ];
+        let mut writer = JsonWriter::new(Vec::new(), false);
+        writer.write_columns(&columns).unwrap();
         writer
-            .write_header(&[
-                "empty".to_string(),
-                "u64_max".to_string(),
-                "scientific".to_string(),
-                "leading_zeros".to_string(),
-                "mixed_case_bool".to_string(),
-            ])
+            .write_values(&[Value::Int(1), Value::Bytes(b"Alice".to_vec())])
             .unwrap();
 
-        // Test various type inference scenarios
+        let first = writer.restart(Vec::new()).unwrap();
+        let second = writer.restart(Vec::new()).unwrap();
+        writer.write_values(&[Value::Int(2), Value::NULL]).unwrap();
+        let third = writer.restart(Vec::new()).unwrap();
+
+        assert_eq!(String::from_utf8(first).unwrap(), r#"{"data":[{"id":1,"name":"Alice"}]}"#);
+        assert_eq!(String::from_utf8(second).unwrap(), r#"{"data":[]}"#);
+        assert_eq!(String::from_utf8(third).unwrap(), r#"{"data":[{"id":2,"name":null}]}"#);
+    }
+
+    #[test]
+    fn test_json_writer_result_sets_are_named_arrays() {
+        let mut output = Vec::new();
+        let mut writer = JsonWriter::with_result_sets(&mut output, false);
         writer
-            .write_row(&[
-                "".to_string(),        // Empty string (should be String, not Null)
-                u64::MAX.to_string(),  // u64::MAX (should be Number)
-                "1.23e-4".to_string(), // Scientific notation (should be Number)
-                "00123".to_string(),   // Leading zeros (should be Number)
-                "TRUE".to_string(),    // Mixed case boolean (should be Bool)
-            ])
+            .write_columns(&[column("id", ColumnType::MYSQL_TYPE_LONG, ColumnFlags::empty())])
             .unwrap();
+        writer.write_values(&[Value::Int(1)]).unwrap();
+        writer.write_values(&[Value::Int(2)]).unwrap();
+        writer.write_columns(&[]).unwrap();
+        writer
+            .write_columns(&[column("name", ColumnType::MYSQL_TYPE_VAR_STRING, ColumnFlags::empty())])
+            .unwrap();
+        writer.write_values(&[Value::Bytes(b"Alice".to_vec())]).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#"{"result_1":[{"id":1},{"id":2}],"result_2":[],"result_3":[{"name":"Alice"}]}"#
+        );
+
+        let mut output = Vec::new();
+        JsonWriter::with_result_sets(&mut output, false).finish().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "{}");
+    }
+
+    /// Builds a column with the given name, type and flags
+    fn column(name: &str, column_type: ColumnType, flags: ColumnFlags) -> Column {
+        Column::new(column_type)
+            .with_name(name.as_bytes())
+            .with_flags(flags)
+            .with_character_set(33)
+    }
 
+    /// Writes a single typed row and returns the parsed first JSON object
+    fn write_typed_row(columns: &[Column], values: &[Value]) -> serde_json::Map<String, serde_json::Value> {
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = JsonWriter::new(&mut cursor, false);
+        writer.write_columns(columns).unwrap();
+        writer.write_values(values).unwrap();
         writer.finalize().unwrap();
 
         let output = String::from_utf8(cursor.into_inner()).unwrap();
-
-        // Parse the JSON to verify type inference
         let json: serde_json::Value = serde_json::from_str(&output).unwrap();
-        let data = json["data"][0].as_object().unwrap();
+        json["data"][0].as_object().unwrap().clone()
+    }
 
-        // Test empty string is preserved as string, not null
-        assert!(data["empty"].is_string());
-        assert_eq!(data["empty"].as_str().unwrap(), "");
+    #[test]
+    fn test_json_type_from_column_metadata() {
+        let columns = [
+            column("zip", ColumnType::MYSQL_TYPE_VAR_STRING, ColumnFlags::empty()),
+            column("flag", ColumnType::MYSQL_TYPE_VAR_STRING, ColumnFlags::empty()),
+            column("count", ColumnType::MYSQL_TYPE_LONG, ColumnFlags::empty()),
+            column("big", ColumnType::MYSQL_TYPE_LONGLONG, ColumnFlags::UNSIGNED_FLAG),
+            column("ratio", ColumnType::MYSQL_TYPE_DOUBLE, ColumnFlags::empty()),
+            column("created", ColumnType::MYSQL_TYPE_DATETIME, ColumnFlags::empty()),
+        ];
+        // Text protocol rows deliver every value as bytes
+        let values = [
+            Value::Bytes(b"00501".to_vec()),
+            Value::Bytes(b"true".to_vec()),
+            Value::Bytes(b"-123".to_vec()),
+            Value::Bytes(u64::MAX.to_string().into_bytes()),
+            Value::Bytes(b"1.23e-4".to_vec()),
+            Value::Bytes(b"2024-01-15 10:30:00".to_vec()),
+        ];
+
+        let data = write_typed_row(&columns, &values);
+
+        // String columns stay strings even when they look like numbers or booleans
+        assert_eq!(data["zip"], serde_json::json!("00501"));
+        assert_eq!(data["flag"], serde_json::json!("true"));
+
+        assert_eq!(data["count"].as_i64().unwrap(), -123);
+        assert_eq!(data["big"].as_u64().unwrap(), u64::MAX);
+        assert_eq!(data["ratio"].as_f64().unwrap(), 1.23e-4);
+        assert_eq!(data["created"], serde_json::json!("2024-01-15 10:30:00"));
+    }
+
+    #[test]
+    fn test_json_decimal_is_exact() {
+        let columns = [column(
+            "amount",
+            ColumnType::MYSQL_TYPE_NEWDECIMAL,
+            ColumnFlags::empty(),
+        )];
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = JsonWriter::new(&mut cursor, false);
+        writer.write_columns(&columns).unwrap();
+        writer
+            .write_values(&[Value::Bytes(b"12345678901234567890.123456789".to_vec())])
+            .unwrap();
+        writer.write_values(&[Value::Bytes(b"-0.50".to_vec())]).unwrap();
+        writer.finalize().unwrap();
 
-        // Test u64::MAX is preserved as number
-        assert!(data["u64_max"].is_number());
-        assert_eq!(data["u64_max"].as_u64().unwrap(), u64::MAX);
+        let output = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(output, r#"{"data":[{"amount":12345678901234567890.123456789},{"amount":-0.50}]}"#);
+    }
 
-        // Test scientific notation is preserved as number
-        assert!(data["scientific"].is_number());
-        assert_eq!(data["scientific"].as_f64().unwrap(), 1.23e-4);
+    #[test]
+    fn test_json_binary_protocol_values() {
+        let columns = [
+            column("id", ColumnType::MYSQL_TYPE_LONG, ColumnFlags::empty()),
+            column("score", ColumnType::MYSQL_TYPE_FLOAT, ColumnFlags::empty()),
+            column("name", ColumnType::MYSQL_TYPE_VAR_STRING, ColumnFlags::empty()),
+        ];
+        let values = [Value::Int(42), Value::Float(2.75), Value::Bytes(b"42".to_vec())];
+
+        let data = write_typed_row(&columns, &values);
+
+        assert_eq!(data["id"], serde_json::json!(42));
+        assert_eq!(data["score"], serde_json::json!(2.75));
+        assert_eq!(data["name"], serde_json::json!("42"));
+    }
 
-        // Test leading zeros are preserved as number
-        assert!(data["leading_zeros"].is_number());
-        assert_eq!(data["leading_zeros"].as_u64().unwrap(), 123);
+    #[test]
+    fn test_json_numeric_fallback_to_string() {
+        let columns = [
+            column("float", ColumnType::MYSQL_TYPE_DOUBLE, ColumnFlags::empty()),
+            column("decimal", ColumnType::MYSQL_TYPE_NEWDECIMAL, ColumnFlags::empty()),
+            column("int", ColumnType::MYSQL_TYPE_LONG, ColumnFlags::empty()),
+        ];
+        let values = [
+            Value::Double(f64::NAN),
+            Value::Bytes(b"1.5e3".to_vec()),
+            Value::Bytes(b"not a number".to_vec()),
+        ];
+
+        let data = write_typed_row(&columns, &values);
+
+        assert_eq!(data["float"], serde_json::json!("NaN"));
+        assert_eq!(data["decimal"], serde_json::json!("1.5e3"));
+        assert_eq!(data["int"], serde_json::json!("not a number"));
+    }
 
-        // Test mixed case boolean is detected
-        assert!(data["mixed_case_bool"].is_boolean());
-        assert!(data["mixed_case_bool"].as_bool().unwrap());
+    #[test]
+    fn test_json_null_is_distinct_from_empty_string() {
+        let columns = [
+            column("email", ColumnType::MYSQL_TYPE_VAR_STRING, ColumnFlags::empty()),
+            column("nickname", ColumnType::MYSQL_TYPE_VAR_STRING, ColumnFlags::empty()),
+            column("age", ColumnType::MYSQL_TYPE_LONG, ColumnFlags::empty()),
+        ];
+        let values = [Value::NULL, Value::Bytes(Vec::new()), Value::NULL];
+
+        let data = write_typed_row(&columns, &values);
+
+        assert!(data["email"].is_null());
+        assert_eq!(data["nickname"], serde_json::json!(""));
+        assert!(data["age"].is_null());
     }
 
     #[test]
-    fn test_json_type_inference_edge_cases() {
+    fn test_json_strings_without_metadata() {
         let mut cursor = Cursor::new(Vec::new());
         let mut writer = JsonWriter::new(&mut cursor, false);
 
         writer
-            .write_header(&[
-                "negative".to_string(),
-                "float".to_string(),
-                "bool_false".to_string(),
-                "mixed_case_false".to_string(),
-                "invalid_float".to_string(),
-            ])
+            .write_header(&["leading_zeros".to_string(), "bool".to_string()])
             .unwrap();
+        writer.write_row(&["00123".to_string(), "TRUE".to_string()]).unwrap();
+        writer.finalize().unwrap();
 
-        // Test edge cases
-        writer
-            .write_row(&[
-                "-123".to_string(),     // Negative integer (should be Number)
-                "1.5".to_string(),      // Float (should be Number)
-                "false".to_string(),    // Lowercase boolean (should be Bool)
-                "FALSE".to_string(),    // Uppercase boolean (should be Bool)
-                "1.23e999".to_string(), // Invalid float (should be String)
-            ])
-            .unwrap();
+        let output = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(output, r#"{"data":[{"leading_zeros":"00123","bool":"TRUE"}]}"#);
+    }
 
+    #[test]
+    fn test_json_pretty_object_layout() {
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = JsonWriter::new(&mut cursor, true);
+
+        writer.write_header(&["a".to_string(), "b".to_string()]).unwrap();
+        writer.write_row(&["1".to_string(), "2".to_string()]).unwrap();
         writer.finalize().unwrap();
 
         let output = String::from_utf8(cursor.into_inner()).unwrap();
-        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
-        let data = json["data"][0].as_object().unwrap();
+        assert_eq!(output, "{\"data\":[{\n  \"a\": \"1\",\n  \"b\": \"2\"\n}]}");
+    }
 
-        // Test negative integer
-        assert!(data["negative"].is_number());
-        assert_eq!(data["negative"].as_i64().unwrap(), -123);
+    #[test]
+    fn test_json_preserves_column_order() {
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = JsonWriter::new(&mut cursor, false);
 
-        // Test float
-        assert!(data["float"].is_number());
-        assert_eq!(data["float"].as_f64().unwrap(), 1.5);
+        writer
+            .write_header(&["zeta".to_string(), "alpha".to_string(), "mid".to_string()])
+            .unwrap();
+        writer
+            .write_row(&["1".to_string(), "2".to_string(), "3".to_string()])
+            .unwrap();
+        writer.finalize().unwrap();
+
+        let output = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(output, r#"{"data":[{"zeta":"1","alpha":"2","mid":"3"}]}"#);
+    }
+
+    #[test]
+    fn test_json_duplicate_columns_are_disambiguated() {
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = JsonWriter::new(&mut cursor, false);
 
-        // Test lowercase boolean
-        assert!(data["bool_false"].is_boolean());
-        assert!(!data["bool_false"].as_bool().unwrap());
+        writer
+            .write_header(&["id".to_string(), "name".to_string(), "id".to_string()])
+            .unwrap();
+        writer
+            .write_row(&["1".to_string(), "order".to_string(), "42".to_string()])
+            .unwrap();
+        writer.finalize().unwrap();
 
-        // Test uppercase boolean
-        assert!(data["mixed_case_false"].is_boolean());
-        assert!(!data["mixed_case_false"].as_bool().unwrap());
+        let output = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(output, r#"{"data":[{"id":"1","name":"order","id_2":"42"}]}"#);
+    }
 
-        // Test invalid float falls back to string
-        assert!(data["invalid_float"].is_string());
-        assert_eq!(data["invalid_float"].as_str().unwrap(), "1.23e999");
+    #[test]
+    fn test_is_json_number() {
+        assert!(is_json_number("0"));
+        assert!(is_json_number("-0.50"));
+        assert!(is_json_number("123.4500"));
+        assert!(!is_json_number("007"));
+        assert!(!is_json_number(".5"));
+        assert!(!is_json_number("5."));
+        assert!(!is_json_number("1e5"));
+        assert!(!is_json_number(""));
     }
 }
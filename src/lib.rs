@@ -1,7 +1,7 @@
-use std::{env, ffi::OsStr, path::Path, sync::Once};
+use std::{collections::HashSet, env, ffi::OsStr, iter::Peekable, path::Path, sync::Once};
 
 use anyhow::{Context, Result};
-use mysql::Row;
+use mysql::{Column, Row, Value};
 
 static INIT: Once = Once::new();
 
@@ -12,52 +12,275 @@ pub fn init_crypto_provider() {
     });
 }
 
+/// Arrow IPC (Feather) output module.
+#[cfg(feature = "arrow")]
+pub mod arrow;
+/// Batch job manifest module.
+pub mod batch;
 /// CLI interface module.
 pub mod cli;
+/// Arrow record batch construction shared by columnar formats.
+#[cfg(any(feature = "parquet", feature = "arrow"))]
+pub(crate) mod columnar;
+/// Output compression module.
+pub mod compression;
+/// Configuration file and connection profile module.
+pub mod config;
 /// CSV output module.
 pub mod csv;
 /// Exit code helper module.
 pub mod exit;
+/// Output format registry module.
+pub mod formats;
+/// HTML table output module.
+pub mod html;
 /// JSON output module.
 pub mod json;
+/// Markdown table output module.
+pub mod markdown;
+/// Newline-delimited JSON output module.
+pub mod ndjson;
+/// Output destination and atomic file module.
+pub mod output;
+/// Bound query parameter module.
+pub mod params;
+/// Apache Parquet output module.
+#[cfg(feature = "parquet")]
+pub mod parquet;
+/// Hive-style partitioned output module.
+pub mod partition;
+/// Multiple result set output module.
+pub mod result_sets;
+/// Transient failure retry module.
+pub mod retry;
+/// Query script statement splitting module.
+pub mod script;
+/// Split output into size-limited parts module.
+pub mod split;
+/// SQL INSERT dump output module.
+pub mod sql;
 /// Tab-delimited output module.
 pub mod tab;
+/// Aligned terminal table output module.
+pub mod table;
+/// Output path template module.
+pub mod template;
+/// Date and time conversion module.
+#[cfg(any(feature = "parquet", feature = "arrow", feature = "xlsx"))]
+pub(crate) mod temporal;
+/// Query timeout and cancellation module.
+pub mod timeout;
 /// TLS configuration module.
 pub mod tls;
+/// Column type classification module.
+pub mod types;
 /// Utility functions module.
 pub mod utils;
+/// Vertical record output module.
+pub mod vertical;
+/// Excel XLSX output module.
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+
+/// Buffer capacity used by every output format writer (64KB)
+pub const OUTPUT_BUFFER_CAPACITY: usize = 64 * 1024;
 
 /// Trait for writing data in different formats
+///
+/// Writers receive the header once, then one row at a time as rows stream from
+/// the database, and must not buffer the whole result set.
 pub trait FormatWriter {
+    /// Writes the column names, called exactly once before any rows
     fn write_header(&mut self, columns: &[String]) -> Result<()>;
+    /// Writes a single data row
     fn write_row(&mut self, row: &[String]) -> Result<()>;
-    fn finalize(self) -> Result<()>;
+
+    /// Writes the header from full column metadata
+    ///
+    /// Type-aware writers override this to capture column types and flags; the default
+    /// writes the column names through [`FormatWriter::write_header`].
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        self.write_header(&column_names(columns))
+    }
+
+    /// Writes a single row of MySQL values
+    ///
+    /// The default converts each value to its string form and calls [`FormatWriter::write_row`].
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        let row = values.iter().map(mysql_value_to_string).collect::<Result<Vec<_>>>()?;
+        self.write_row(&row)
+    }
+
+    /// Writes any trailing output and flushes the underlying writer
+    ///
+    /// This is the object-safe form of [`FormatWriter::finalize`] used through `Box<dyn FormatWriter>`.
+    fn finish(&mut self) -> Result<()>;
+
+    /// Finishes the output and consumes the writer
+    fn finalize(mut self) -> Result<()>
+    where
+        Self: Sized,
+    {
+        self.finish()
+    }
+}
+
+impl<T: FormatWriter + ?Sized> FormatWriter for Box<T> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        (**self).write_header(columns)
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        (**self).write_row(row)
+    }
+
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        (**self).write_columns(columns)
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        (**self).write_values(values)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        (**self).finish()
+    }
 }
 
-/// Trait for streaming data processing (future enhancement)
+/// Format writer that can end its current output and continue on a new sink
 ///
-/// This trait will enable memory-efficient processing of large result sets
-/// by processing rows one at a time instead of loading everything into memory.
-pub trait StreamingProcessor {
-    type Item;
-    type Error;
+/// Used to split one result set across several files: each output is complete and
+/// standalone, with its own header or document envelope.
+pub trait RestartableWriter<W: std::io::Write>: FormatWriter {
+    /// Finishes the current output, then starts a new one on `writer` with the same header
+    ///
+    /// Must be called after the header has been written. Returns the previous sink, flushed,
+    /// so the caller can close it.
+    fn restart(&mut self, writer: W) -> Result<W>;
+
+    /// Writes any buffered rows through to the sink, so its size can be measured
+    fn flush(&mut self) -> Result<()>;
+
+    /// Finishes the current output and returns its sink, flushed
+    fn into_inner(self: Box<Self>) -> Result<W>;
+}
+
+/// Streams MySQL rows as validated value records, one row at a time.
+///
+/// This keeps memory usage proportional to a single row regardless of the size of
+/// the result set (F007). Each value is checked before it is yielded so writers can
+/// rely on it converting cleanly. Iteration stops at the first driver or type
+/// conversion error, which is then reported by [`RowStream::finish`].
+pub struct RowStream<I: Iterator<Item = mysql::Result<Row>>> {
+    rows: Peekable<I>,
+    rows_streamed: u64,
+    error: Option<anyhow::Error>,
+}
+
+impl<I: Iterator<Item = mysql::Result<Row>>> RowStream<I> {
+    /// Creates a new stream over the given MySQL rows
+    pub fn new(rows: I) -> Self {
+        Self {
+            rows: rows.peekable(),
+            rows_streamed: 0,
+            error: None,
+        }
+    }
 
-    /// Process a single item from the stream
-    fn process_item(&mut self, item: Self::Item) -> std::result::Result<(), Self::Error>;
+    /// Returns true if the underlying result set has no rows left
+    pub fn is_empty(&mut self) -> bool {
+        self.rows.peek().is_none()
+    }
+
+    /// Returns the number of rows yielded so far
+    pub fn rows_streamed(&self) -> u64 {
+        self.rows_streamed
+    }
 
-    /// Finalize the streaming operation
-    fn finalize(self) -> std::result::Result<(), Self::Error>;
+    /// Finishes the stream, returning the number of rows yielded or the error that ended it
+    pub fn finish(self) -> anyhow::Result<u64> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.rows_streamed),
+        }
+    }
 }
 
-// TODO: Implement RowStream with correct QueryResult type signature
-// pub struct RowStream<'a> {
-//     result: mysql::QueryResult<'a>,
-//     columns: Vec<Column>,
-// }
+impl<I: Iterator<Item = mysql::Result<Row>>> Iterator for RowStream<I> {
+    type Item = Vec<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        let converted = match self.rows.next()? {
+            Ok(row) => validate_row(row, self.rows_streamed as usize),
+            Err(e) => Err(anyhow::Error::new(e)),
+        };
+
+        match converted {
+            Ok(data_row) => {
+                self.rows_streamed += 1;
+                Some(data_row)
+            },
+            Err(e) => {
+                self.error = Some(e);
+                None
+            },
+        }
+    }
+}
+
+/// Extracts the column names from MySQL column metadata, preserving database order.
+///
+/// # Arguments
+///
+/// * `columns` - The column metadata of a result set.
+///
+/// # Returns
+///
+/// A vector of column names suitable for use as a header row.
+pub fn column_names(columns: &[Column]) -> Vec<String> {
+    columns.iter().map(|column| column.name_str().to_string()).collect()
+}
+
+/// Makes column names unique for use as object keys or field names, preserving their order
+///
+/// The first occurrence of a name is kept as-is; later duplicates get the smallest suffix
+/// `_2`, `_3`, ... that does not collide with any other column name.
+pub fn unique_column_names(columns: &[String]) -> Vec<String> {
+    let taken: HashSet<&str> = columns.iter().map(String::as_str).collect();
+    let mut seen: HashSet<&str> = HashSet::with_capacity(columns.len());
+    let mut generated: HashSet<String> = HashSet::new();
+    let mut keys = Vec::with_capacity(columns.len());
+
+    for column in columns {
+        if seen.insert(column.as_str()) {
+            keys.push(column.clone());
+            continue;
+        }
+
+        let mut suffix = 2;
+        let key = loop {
+            let candidate = format!("{}_{}", column, suffix);
+            if !taken.contains(candidate.as_str()) && !generated.contains(&candidate) {
+                break candidate;
+            }
+            suffix += 1;
+        };
+        generated.insert(key.clone());
+        keys.push(key);
+    }
+
+    keys
+}
 
 /// Converts MySQL rows to a vector of string vectors, with the first row as headers.
 ///
 /// This function safely handles all MySQL data types including NULL values without panicking.
+/// NULL values become empty strings, so callers that need to tell NULL apart from an empty
+/// string should stream values through [`RowStream`] and [`FormatWriter::write_values`] instead.
 /// It uses safe iteration over row values instead of indexed access to prevent runtime panics.
 ///
 /// # Arguments
@@ -82,35 +305,55 @@ pub fn rows_to_strings(rows: Vec<Row>) -> anyhow::Result<Vec<Vec<String>>> {
     let mut result_rows = Vec::with_capacity(rows.len() + 1);
 
     // Extract headers from the first row
-    let header_row: Vec<String> = rows[0]
-        .columns_ref()
-        .iter()
-        .map(|column| column.name_str().to_string())
-        .collect();
-    result_rows.push(header_row);
+    result_rows.push(column_names(rows[0].columns_ref()));
 
     // Process each row using safe iteration
     for (row_index, row) in rows.iter().enumerate() {
-        let mut data_row = Vec::with_capacity(row.len());
-        for i in 0..row.len() {
-            match row.as_ref(i) {
-                Some(value) => match mysql_value_to_string(value) {
-                    Ok(string_value) => data_row.push(string_value),
-                    Err(e) => {
-                        return Err(e.context(format!(
-                            "Type conversion failed at row {} column {}",
-                            row_index + 1,
-                            i + 1
-                        )));
-                    },
+        result_rows.push(row_to_strings(row, row_index)?);
+    }
+
+    Ok(result_rows)
+}
+
+/// Converts a single MySQL row to its string values using safe iteration.
+///
+/// `row_index` is the zero-based position of the row in its result set and is
+/// only used to give conversion errors a useful location.
+fn row_to_strings(row: &Row, row_index: usize) -> anyhow::Result<Vec<String>> {
+    let mut data_row = Vec::with_capacity(row.len());
+    for i in 0..row.len() {
+        match row.as_ref(i) {
+            Some(value) => match mysql_value_to_string(value) {
+                Ok(string_value) => data_row.push(string_value),
+                Err(e) => {
+                    return Err(e.context(format!(
+                        "Type conversion failed at row {} column {}",
+                        row_index + 1,
+                        i + 1
+                    )));
                 },
-                None => data_row.push(String::new()),
-            }
+            },
+            None => data_row.push(String::new()),
         }
-        result_rows.push(data_row);
     }
+    Ok(data_row)
+}
 
-    Ok(result_rows)
+/// Unwraps a MySQL row into its values, checking that each one can be converted.
+///
+/// Values already taken from the row are returned as NULL.
+fn validate_row(row: Row, row_index: usize) -> anyhow::Result<Vec<Value>> {
+    let values: Vec<Value> = row
+        .unwrap_raw()
+        .into_iter()
+        .map(|value| value.unwrap_or(Value::NULL))
+        .collect();
+    for (i, value) in values.iter().enumerate() {
+        if let Err(e) = validate_value(value) {
+            return Err(e.context(format!("Type conversion failed at row {} column {}", row_index + 1, i + 1)));
+        }
+    }
+    Ok(values)
 }
 
 /// Safely converts a MySQL Value to a String representation.
@@ -133,7 +376,7 @@ pub fn rows_to_strings(rows: Vec<Row>) -> anyhow::Result<Vec<Vec<String>>> {
 ///
 /// Returns an error for certain edge cases such as invalid date/time values
 /// that cannot be properly formatted.
-fn mysql_value_to_string(value: &mysql::Value) -> anyhow::Result<String> {
+pub(crate) fn mysql_value_to_string(value: &mysql::Value) -> anyhow::Result<String> {
     match value {
         mysql::Value::NULL => Ok(String::new()),
         mysql::Value::Bytes(bytes) => {
@@ -177,6 +420,45 @@ fn mysql_value_to_string(value: &mysql::Value) -> anyhow::Result<String> {
             }
         },
         mysql::Value::Date(year, month, day, hour, minute, second, microsecond) => {
+            validate_value(value)?;
+
+            if *hour == 0 && *minute == 0 && *second == 0 && *microsecond == 0 {
+                Ok(format!("{:04}-{:02}-{:02}", year, month, day))
+            } else {
+                Ok(format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}",
+                    year, month, day, hour, minute, second, microsecond
+                ))
+            }
+        },
+        mysql::Value::Time(negative, days, hours, minutes, seconds, microseconds) => {
+            validate_value(value)?;
+
+            let sign = if *negative { "-" } else { "" };
+            if *days > 0 {
+                Ok(format!(
+                    "{}{:02}:{:02}:{:02}.{:06}",
+                    sign,
+                    days * 24 + *hours as u32,
+                    minutes,
+                    seconds,
+                    microseconds
+                ))
+            } else {
+                Ok(format!("{}{:02}:{:02}:{:02}.{:06}", sign, hours, minutes, seconds, microseconds))
+            }
+        },
+    }
+}
+
+/// Checks that a MySQL value holds a representable date or time.
+///
+/// # Errors
+///
+/// Returns a type conversion error for out-of-range date and time components.
+fn validate_value(value: &mysql::Value) -> anyhow::Result<()> {
+    match value {
+        mysql::Value::Date(_, month, day, hour, minute, second, microsecond) => {
             // Add validation for date values (requirement 10.3)
             if *month == 0 || *month > 12 {
                 anyhow::bail!("Type conversion error: Invalid month value {} in date", month);
@@ -196,17 +478,8 @@ fn mysql_value_to_string(value: &mysql::Value) -> anyhow::Result<String> {
             if *microsecond > 999999 {
                 anyhow::bail!("Type conversion error: Invalid microsecond value {} in datetime", microsecond);
             }
-
-            if *hour == 0 && *minute == 0 && *second == 0 && *microsecond == 0 {
-                Ok(format!("{:04}-{:02}-{:02}", year, month, day))
-            } else {
-                Ok(format!(
-                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}",
-                    year, month, day, hour, minute, second, microsecond
-                ))
-            }
         },
-        mysql::Value::Time(negative, days, hours, minutes, seconds, microseconds) => {
+        mysql::Value::Time(_, _, hours, minutes, seconds, microseconds) => {
             // Add validation for time values (requirement 10.3)
             if *hours > 23 {
                 anyhow::bail!("Type conversion error: Invalid hour value {} in time", hours);
@@ -220,22 +493,10 @@ fn mysql_value_to_string(value: &mysql::Value) -> anyhow::Result<String> {
             if *microseconds > 999999 {
                 anyhow::bail!("Type conversion error: Invalid microsecond value {} in time", microseconds);
             }
-
-            let sign = if *negative { "-" } else { "" };
-            if *days > 0 {
-                Ok(format!(
-                    "{}{:02}:{:02}:{:02}.{:06}",
-                    sign,
-                    days * 24 + *hours as u32,
-                    minutes,
-                    seconds,
-                    microseconds
-                ))
-            } else {
-                Ok(format!("{}{:02}:{:02}:{:02}.{:06}", sign, hours, minutes, seconds, microseconds))
-            }
         },
+        _ => {},
     }
+    Ok(())
 }
 
 /// Extracts the file extension from a filename, if present.
@@ -251,6 +512,14 @@ pub fn get_extension_from_filename(filename: &str) -> Option<&str> {
     Path::new(filename).extension().and_then(OsStr::to_str)
 }
 
+/// Output path that selects standard output instead of a file
+pub const STDOUT_PATH: &str = "-";
+
+/// Returns true if the output path is `-`, meaning results are written to stdout.
+pub fn is_stdout(path: &Path) -> bool {
+    path.as_os_str() == STDOUT_PATH
+}
+
 /// Gets a required environment variable with contextual error information.
 ///
 /// # Arguments
@@ -268,6 +537,13 @@ pub fn get_required_env(var_name: &str) -> Result<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_stdout() {
+        assert!(is_stdout(Path::new("-")));
+        assert!(!is_stdout(Path::new("./-")));
+        assert!(!is_stdout(Path::new("out.csv")));
+    }
+
     #[test]
     fn test_get_required_env_missing() {
         let result = get_required_env("NONEXISTENT_ENV_VAR");
@@ -366,6 +642,78 @@ mod tests {
         assert_eq!(result.len(), 0);
     }
 
+    /// Builds a MySQL row with text columns named after `names`
+    fn build_row(names: &[&str], values: Vec<mysql::Value>) -> Row {
+        let columns: Vec<Column> = names
+            .iter()
+            .map(|name| Column::new(mysql::consts::ColumnType::MYSQL_TYPE_VAR_STRING).with_name(name.as_bytes()))
+            .collect();
+        mysql_common::row::new_row(values, columns.into())
+    }
+
+    #[test]
+    fn test_column_names_preserve_order() {
+        let row = build_row(&["zeta", "alpha", "id"], vec![mysql::Value::NULL; 3]);
+        assert_eq!(column_names(row.columns_ref()), vec!["zeta", "alpha", "id"]);
+    }
+
+    #[test]
+    fn test_unique_column_names_avoid_existing_names() {
+        let columns = ["id", "id_2", "id", "id"].map(String::from);
+        assert_eq!(unique_column_names(&columns), vec!["id", "id_2", "id_3", "id_4"]);
+    }
+
+    #[test]
+    fn test_row_stream_converts_rows_lazily() {
+        let rows = vec![
+            Ok(build_row(&["id", "name"], vec![mysql::Value::Int(1), mysql::Value::Bytes(b"Alice".to_vec())])),
+            Ok(build_row(&["id", "name"], vec![mysql::Value::Int(2), mysql::Value::NULL])),
+        ];
+        let mut stream = RowStream::new(rows.into_iter());
+
+        assert!(!stream.is_empty());
+        assert_eq!(stream.next(), Some(vec![mysql::Value::Int(1), mysql::Value::Bytes(b"Alice".to_vec())]));
+        assert_eq!(stream.rows_streamed(), 1);
+        assert_eq!(stream.next(), Some(vec![mysql::Value::Int(2), mysql::Value::NULL]));
+        assert_eq!(stream.next(), None);
+        assert_eq!(stream.finish().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_row_stream_empty() {
+        let mut stream = RowStream::new(std::iter::empty());
+        assert!(stream.is_empty());
+        assert_eq!(stream.next(), None);
+        assert_eq!(stream.finish().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_row_stream_stops_on_conversion_error() {
+        let rows = vec![
+            Ok(build_row(&["created"], vec![mysql::Value::Date(2023, 12, 25, 0, 0, 0, 0)])),
+            Ok(build_row(&["created"], vec![mysql::Value::Date(2023, 13, 25, 0, 0, 0, 0)])),
+            Ok(build_row(&["created"], vec![mysql::Value::Date(2023, 12, 26, 0, 0, 0, 0)])),
+        ];
+        let mut stream = RowStream::new(rows.into_iter());
+
+        let collected: Vec<Vec<mysql::Value>> = stream.by_ref().collect();
+        assert_eq!(collected, vec![vec![mysql::Value::Date(2023, 12, 25, 0, 0, 0, 0)]]);
+
+        let error = stream.finish().unwrap_err();
+        assert!(error.to_string().contains("Type conversion failed at row 2 column 1"));
+    }
+
+    #[test]
+    fn test_row_stream_propagates_driver_error() {
+        let rows = vec![Err(mysql::Error::DriverError(mysql::DriverError::ConnectTimeout))];
+        let mut stream = RowStream::new(rows.into_iter());
+
+        assert!(!stream.is_empty());
+        assert_eq!(stream.next(), None);
+        let error = stream.finish().unwrap_err();
+        assert!(error.downcast_ref::<mysql::Error>().is_some());
+    }
+
     #[test]
     fn test_rows_to_strings_type_conversion_error() {
         // This test demonstrates that type conversion errors are propagated correctly
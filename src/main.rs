@@ -1,17 +1,39 @@
-use std::{env, fs::File, path::PathBuf};
+// The --dump-config json! object has more keys than the default limit allows
+#![recursion_limit = "256"]
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use clap::{CommandFactory, Parser};
 use clap_complete::{Shell as CompletionShell, generate};
-use mysql::Pool;
-use mysql::prelude::Queryable;
-
-use gold_digger::cli::{Cli, Commands, OutputFormat, Shell};
-use gold_digger::exit::{exit_no_rows, exit_success, exit_with_error};
-use gold_digger::rows_to_strings;
+use mysql::prelude::{Protocol, Queryable};
+use mysql::{Column, Pool, PooledConn, QueryResult, Value};
+
+use gold_digger::batch::{BatchJob, load_manifest};
+use gold_digger::cli::{BatchArgs, Cli, Commands, Compression, OutputFormat, ResultSetMode, Shell};
+use gold_digger::compression::{CompressedWriter, check_compression, strip_compression_extension};
+use gold_digger::exit::{
+    EXIT_NO_ROWS, EXIT_SUCCESS, exit_no_rows, exit_success, exit_with_error, is_broken_pipe, map_error_to_exit_code,
+};
+use gold_digger::formats::{WriterOptions, create_writer};
+use gold_digger::output::Destination;
+use gold_digger::params::{BoundStatement, QueryParams};
+use gold_digger::partition::{DEFAULT_MAX_OPEN_PARTITIONS, PartitionedOutput};
+use gold_digger::result_sets::{CombinedOutput, result_set_path};
+use gold_digger::retry::{Operation, RetryPolicy, Transient, transient_error};
+use gold_digger::script::{check_read_only, is_read_statement, split_statements};
+use gold_digger::split::{SplitLimits, SplitOutput};
+use gold_digger::template::{TemplateValues, expand_template, is_template};
+use gold_digger::timeout::{QueryWatchdog, format_duration, set_session_timeout};
 use gold_digger::utils::redact_sql_error;
+use gold_digger::{FormatWriter, RowStream};
 
-use gold_digger::tls::{TlsConfig, create_tls_connection};
+use gold_digger::tls::{TlsConfig, create_tls_connection_with_retry};
 
 /// Main entry point for the gold_digger CLI tool.
 ///
@@ -20,16 +42,17 @@ fn main() {
     // Initialize crypto provider for rustls
     gold_digger::init_crypto_provider();
 
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
 
-    // Handle subcommands first
-    if let Some(command) = cli.command {
-        match command {
-            Commands::Completion { shell } => {
-                generate_completion(shell);
-                return;
-            },
-        }
+    // Handle subcommands that need no configuration first
+    if let Some(Commands::Completion { shell }) = &cli.command {
+        generate_completion(shell.clone());
+        return;
+    }
+
+    // Fill in unset options from --profile: CLI flags > environment variables > profile > defaults
+    if let Err(e) = gold_digger::config::apply_profile(&mut cli) {
+        exit_with_error(e, Some("Configuration profile loading failed"));
     }
 
     // Handle --dump-config flag
@@ -40,116 +63,554 @@ fn main() {
         return;
     }
 
+    if let Some(Commands::Batch(args)) = cli.command.take() {
+        run_batch(&cli, &args);
+    }
+
     // Resolve configuration with precedence: CLI flags > environment variables
     let database_url = match resolve_database_url(&cli) {
         Ok(url) => url,
         Err(e) => exit_with_error(e, Some("Database URL resolution failed")),
     };
-    let database_query = match resolve_database_query(&cli) {
-        Ok(query) => query,
-        Err(e) => exit_with_error(e, Some("Database query resolution failed")),
-    };
-    let output_file = match resolve_output_file(&cli) {
-        Ok(file) => file,
-        Err(e) => exit_with_error(e, Some("Output file resolution failed")),
-    };
+    let plan = plan_run(&cli).unwrap_or_else(|e| e.exit());
 
     let pool = match create_database_connection(&database_url, &cli) {
         Ok(pool) => pool,
         Err(e) => exit_with_error(anyhow::anyhow!("Database connection pool creation failed: {}", e), None),
     };
-    let mut conn = match pool.get_conn() {
+    let retry = retry_policy(&cli, &database_url);
+    let mut conn = match retry.run(Operation::Connect, || pool.get_conn()) {
         Ok(conn) => conn,
         Err(e) => exit_with_error(anyhow::anyhow!("Database connection failed: {}", e), None),
     };
 
     if cli.verbose > 0 && !cli.quiet {
-        println!("Connecting to database...");
-    }
-
-    let result: Vec<mysql::Row> = match conn.query(&database_query) {
-        Ok(result) => result,
-        Err(e) => {
-            // Structured error matching on mysql::Error variants
-            let (context, _should_show_details) = match &e {
-                mysql::Error::MySqlError(mysql_err) => {
-                    // Map known MySQL error codes to contextual messages
-                    let context = match mysql_err.code {
-                        1064 => "SQL syntax error in query",                   // ER_PARSE_ERROR
-                        1146 => "Table does not exist",                        // ER_NO_SUCH_TABLE
-                        1054 => "Column does not exist or is ambiguous",       // ER_BAD_FIELD_ERROR
-                        1045 => "Access denied - invalid credentials",         // ER_ACCESS_DENIED_ERROR
-                        1044 => "Access denied to database",                   // ER_DBACCESS_DENIED_ERROR
-                        1142 => "Insufficient privileges for query execution", // ER_TABLEACCESS_DENIED_ERROR
-                        1143 => "Insufficient column privileges",              // ER_COLUMNACCESS_DENIED_ERROR
-                        1049 => "Unknown database",                            // ER_BAD_DB_ERROR
-                        2002 => "Connection failed - server not reachable",    // CR_CONNECTION_ERROR
-                        2003 => "Connection failed - server not responding",   // CR_CONN_HOST_ERROR
-                        2006 => "Connection lost - server has gone away",      // CR_SERVER_GONE_ERROR
-                        2013 => "Connection lost during query",                // CR_SERVER_LOST
-                        _ => "Query execution failed",
-                    };
-                    (context, true)
-                },
-                mysql::Error::IoError(_) => ("Network I/O error during query execution", false),
-                mysql::Error::UrlError(_) => ("Invalid database URL format", false),
-                mysql::Error::DriverError(_) => ("Database driver error", false),
-                _ => ("Query execution failed", false),
-            };
+        eprintln!("Connecting to database...");
+    }
 
-            // Create error message with appropriate level of detail
-            let error_message = if cli.verbose > 0 && _should_show_details {
-                format!("{}: {}", context, redact_sql_error(&e.to_string()))
-            } else {
-                context.to_string()
-            };
+    match execute_run(&cli, &plan, &pool, &mut conn, &retry, |e| e.exit()) {
+        Ok(RunOutcome::Written { .. }) => exit_success(None),
+        Ok(RunOutcome::NoRows) => exit_no_rows(Some("No records found in database")),
+        Err(e) => e.exit(),
+    }
+}
+
+/// Context of errors raised while writing output
+const OUTPUT_ERROR_CONTEXT: &str = "Output writing failed";
+
+/// An error that ended a run, with the context it is reported under
+struct RunError {
+    error: anyhow::Error,
+    context: Option<&'static str>,
+}
+
+type RunResult<T> = std::result::Result<T, RunError>;
+
+impl RunError {
+    fn new(error: anyhow::Error, context: Option<&'static str>) -> Self {
+        Self { error, context }
+    }
+
+    /// A failure while writing output
+    fn output(error: anyhow::Error) -> Self {
+        Self::new(error, Some(OUTPUT_ERROR_CONTEXT))
+    }
+
+    /// A failed query, described without leaking SQL unless running verbosely
+    fn query(error: &mysql::Error, cli: &Cli) -> Self {
+        Self::new(describe_query_error(error, cli), Some("Database query failed"))
+    }
+
+    /// A query cancelled because it ran past --query-timeout
+    fn timed_out(timeout: Duration) -> Self {
+        Self::new(
+            anyhow::anyhow!("Query timed out after {} (--query-timeout)", format_duration(timeout)),
+            Some("Database query failed"),
+        )
+    }
+
+    /// Returns the exit code the error maps to
+    fn exit_code(&self) -> i32 {
+        map_error_to_exit_code(&self.error)
+    }
+
+    /// Exits with the error
+    ///
+    /// A closed pipe (for example `gold_digger -o - ... | head`) means the reader has all the
+    /// output it wants, so it ends the run successfully instead of being reported as an error.
+    fn exit(self) -> ! {
+        if self.context == Some(OUTPUT_ERROR_CONTEXT) && is_broken_pipe(&self.error) {
+            exit_success(None);
+        }
+        exit_with_error(self.error, self.context)
+    }
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.context {
+            Some(context) => write!(f, "{}: {}", context, self.error),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+/// Adds the context a step's errors are reported under
+trait RunContext<T> {
+    fn run_context(self, context: &'static str) -> RunResult<T>;
+}
+
+impl<T> RunContext<T> for Result<T> {
+    fn run_context(self, context: &'static str) -> RunResult<T> {
+        self.map_err(|e| RunError::new(e, Some(context)))
+    }
+}
+
+/// Everything a run needs besides the connection, resolved before connecting
+struct RunPlan {
+    output_file: PathBuf,
+    format: OutputFormat,
+    compression: Compression,
+    layout: OutputLayout,
+    result_set_mode: ResultSetMode,
+    statements: Vec<BoundStatement>,
+}
+
+/// Resolves the query, its parameters and the output of a run
+fn plan_run(cli: &Cli) -> RunResult<RunPlan> {
+    let database_query = resolve_database_query(cli).run_context("Database query resolution failed")?;
+    let params =
+        QueryParams::load(cli.params_file.as_deref(), &cli.params).run_context("Query parameter loading failed")?;
+    let output_file = resolve_output_file(cli)
+        .and_then(|file| prepare_output_file(cli, file, &params, Utc::now()))
+        .run_context("Output file resolution failed")?;
+    let format = resolve_output_format(cli, &output_file).run_context("Output format resolution failed")?;
+    let compression = resolve_compression(cli, &output_file).run_context("Output compression resolution failed")?;
+    let layout = resolve_output_layout(cli, &output_file, format).run_context("Output layout resolution failed")?;
+    let result_set_mode =
+        resolve_result_set_mode(cli, format, &layout).run_context("Output layout resolution failed")?;
+    let statements = split_statements(&database_query).run_context("Database query resolution failed")?;
+    if !cli.allow_writes {
+        check_read_only(&statements).run_context("Database query resolution failed")?;
+    }
+    let statements = params.bind(&statements).run_context("Query parameter binding failed")?;
+    Ok(RunPlan {
+        output_file,
+        format,
+        compression,
+        layout,
+        result_set_mode,
+        statements,
+    })
+}
+
+/// What a run wrote
+enum RunOutcome {
+    /// Output was written, possibly empty with `--allow-empty`
+    Written {
+        records: u64,
+        result_sets: usize,
+        written_to: String,
+    },
+    /// No result set had any rows, so nothing was written
+    NoRows,
+}
 
-            exit_with_error(anyhow::anyhow!("{}", error_message), Some("Database query failed"));
+/// Runs the statements of a plan and writes their result sets
+///
+/// Errors raised while a result is being read are passed to `on_error` before the result is
+/// dropped. Dropping an unfinished result reads it to the end, which keeps the connection
+/// usable for a batch but would hold up a single run, so that exits from `on_error` instead.
+///
+/// Unless --allow-writes is given, the statements run in a read-only transaction.
+///
+/// With --query-timeout, the statements share one deadline. The session's server-side limit
+/// is set to it, and a watchdog cancels the query from a second connection of `pool` when it
+/// passes, which also covers statements the server does not limit. No statement starts once
+/// the deadline has passed.
+///
+/// A statement that fails with a transient error before returning a result is run again as
+/// `retry` allows, on a new connection if the old one was lost. Retries count towards the
+/// same deadline, and a new connection is only given the time that is left. Only read statements are
+/// retried, and only while every statement before them was a read too, so a retry never
+/// repeats a change or runs outside a transaction the script started.
+fn execute_run(
+    cli: &Cli,
+    plan: &RunPlan,
+    pool: &Pool,
+    conn: &mut PooledConn,
+    retry: &RetryPolicy,
+    on_error: fn(RunError) -> RunError,
+) -> RunResult<RunOutcome> {
+    let combined = match plan.result_set_mode {
+        ResultSetMode::Combined => {
+            let options = writer_options(cli, &plan.output_file);
+            Some(
+                CombinedOutput::open(
+                    &plan.output_file,
+                    plan.format,
+                    plan.compression,
+                    cli.compression_level,
+                    &options,
+                )
+                .map_err(|e| RunError::new(e, None))?,
+            )
         },
+        ResultSetMode::Numbered => None,
+    };
+    let mut outputs = ResultSetOutputs {
+        cli,
+        output_file: &plan.output_file,
+        format: plan.format,
+        compression: plan.compression,
+        layout: &plan.layout,
+        combined,
+        outputs: Vec::new(),
+        written: 0,
+        empty_sets: Vec::new(),
+        record_count: 0,
     };
 
-    if cli.verbose > 0 && !cli.quiet {
-        println!("Outputting {} records to {}.", result.len(), output_file.display());
+    let deadline = cli.query_timeout.map(|timeout| (Instant::now() + timeout, timeout));
+    let mut watchdog = prepare_session(cli, pool, conn, deadline).map_err(|e| RunError::query(&e, cli))?;
+    let retryable = plan
+        .statements
+        .iter()
+        .take_while(|statement| is_read_statement(&statement.sql))
+        .count();
+
+    for (index, statement) in plan.statements.iter().enumerate() {
+        let mut retries = 0;
+        let mut reconnect = false;
+        loop {
+            if let Some((deadline, timeout)) = deadline
+                && Instant::now() >= deadline
+            {
+                return Err(RunError::timed_out(timeout));
+            }
+            let attempt = if reconnect {
+                drop(watchdog.take());
+                pool.get_conn().and_then(|fresh| {
+                    *conn = fresh;
+                    watchdog = prepare_session(cli, pool, conn, deadline)?;
+                    Ok(())
+                })
+            } else {
+                Ok(())
+            };
+            let handle = |e| on_error(check_timeout(e, &watchdog, cli));
+            let error = match attempt.and_then(|()| run_statement(conn, statement, &mut outputs, &handle)) {
+                Ok(exported) => break exported?,
+                Err(error) => error,
+            };
+            let transient = transient_error(&error);
+            // A statement cancelled by the watchdog is out of time, so it is not retried
+            let expired = watchdog.as_ref().is_some_and(QueryWatchdog::expired);
+            if transient.is_none() || index >= retryable || retries == retry.retries || expired {
+                return Err(check_timeout(RunError::query(&error, cli), &watchdog, cli));
+            }
+            retries += 1;
+            retry.wait(Operation::Query, retries, &error);
+            reconnect = transient == Some(Transient::Connection);
+        }
     }
+    drop(watchdog);
 
-    if result.is_empty() {
-        if cli.allow_empty {
+    let record_count = outputs.record_count;
+    if record_count == 0 {
+        if !cli.allow_empty {
             if cli.verbose > 0 && !cli.quiet {
-                println!("No records found in database, but --allow-empty is set.");
+                eprintln!("No records found in database.");
             }
+            // Dropping the uncommitted output removes its temporary file
+            return Ok(RunOutcome::NoRows);
+        }
+        if cli.verbose > 0 && !cli.quiet {
+            eprintln!("No records found in database, but --allow-empty is set.");
+        }
+        if outputs.written == 0 && outputs.combined.is_none() {
             // Create empty output file
-            let output = match File::create(&output_file) {
-                Ok(output) => output,
-                Err(e) => exit_with_error(anyhow::anyhow!("Failed to create output file: {}", e), None),
-            };
-            let empty_rows: Vec<Vec<String>> = vec![];
-            if let Err(e) = write_output(empty_rows, output, output_file.as_path(), &cli) {
-                exit_with_error(e, Some("Output writing failed"));
-            }
+            outputs.write(&[], std::iter::empty())?;
+        }
+    }
+
+    let result_sets = outputs.written;
+    let written_to = outputs.commit()?;
+    if cli.verbose > 0 && !cli.quiet && record_count > 0 {
+        if result_sets > 1 {
+            eprintln!("Wrote {} records from {} result sets to {}.", record_count, result_sets, written_to);
         } else {
+            eprintln!("Wrote {} records to {}.", record_count, written_to);
+        }
+    }
+    Ok(RunOutcome::Written {
+        records: record_count,
+        result_sets,
+        written_to,
+    })
+}
+
+/// Runs one statement and exports its result sets
+///
+/// Returns the driver error if the statement fails before returning a result, which leaves
+/// nothing written so the statement can be retried, or the outcome of exporting it otherwise.
+fn run_statement(
+    conn: &mut PooledConn,
+    statement: &BoundStatement,
+    outputs: &mut ResultSetOutputs,
+    on_error: &dyn Fn(RunError) -> RunError,
+) -> mysql::Result<RunResult<()>> {
+    let exported = match &statement.params {
+        Some(params) => {
+            let mut result = conn.exec_iter(statement.sql.as_str(), params.clone())?;
+            outputs.export(&mut result).map_err(on_error)
+        },
+        None => {
+            let mut result = conn.query_iter(statement.sql.as_str())?;
+            outputs.export(&mut result).map_err(on_error)
+        },
+    };
+    Ok(exported)
+}
+
+/// Prepares a connection for a run, returning the watchdog that enforces --query-timeout
+///
+/// In read-only mode the session's transactions are made read-only (setting its
+/// `transaction_read_only`) and one is started, so writes the static check cannot see, such as
+/// those of a called procedure, fail on the server. The session stays read-only even if a
+/// procedure ends the transaction.
+///
+/// `deadline` is when the run's --query-timeout passes, with the timeout itself; the session
+/// limit and the watchdog get the time left until then.
+fn prepare_session(
+    cli: &Cli,
+    pool: &Pool,
+    conn: &mut PooledConn,
+    deadline: Option<(Instant, Duration)>,
+) -> mysql::Result<Option<QueryWatchdog>> {
+    if !cli.allow_writes {
+        conn.query_drop("SET SESSION TRANSACTION READ ONLY")?;
+        conn.query_drop("START TRANSACTION READ ONLY")?;
+    }
+    let Some((deadline, _)) = deadline else {
+        return Ok(None);
+    };
+    // A limit of zero would turn the server's limit off
+    let remaining = deadline
+        .saturating_duration_since(Instant::now())
+        .max(Duration::from_millis(1));
+    set_session_timeout(conn, Some(remaining))?;
+    Ok(Some(QueryWatchdog::start(pool, conn.connection_id(), remaining)))
+}
+
+/// Undoes what [`prepare_session`] set up, so the next batch job starts from a plain session
+///
+/// Errors are ignored: a connection that cannot be reset is replaced after a failed job.
+fn reset_session(cli: &Cli, conn: &mut PooledConn) {
+    if !cli.allow_writes {
+        let _ = conn.query_drop("ROLLBACK");
+        let _ = conn.query_drop("SET SESSION TRANSACTION READ WRITE");
+    }
+    if cli.query_timeout.is_some() {
+        let _ = set_session_timeout(conn, None);
+    }
+}
+
+/// Reports an error caused by the watchdog cancelling the query as the timeout
+fn check_timeout(error: RunError, watchdog: &Option<QueryWatchdog>, cli: &Cli) -> RunError {
+    match (watchdog, cli.query_timeout) {
+        (Some(watchdog), Some(timeout)) if watchdog.expired() => RunError::timed_out(timeout),
+        _ => error,
+    }
+}
+
+/// How a batch job ended
+enum JobStatus {
+    Finished(RunOutcome),
+    Failed(RunError),
+    /// Not run because another job failed and --keep-going was not given
+    Skipped,
+}
+
+impl JobStatus {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::Finished(RunOutcome::Written { .. }) | Self::Skipped => EXIT_SUCCESS,
+            Self::Finished(RunOutcome::NoRows) => EXIT_NO_ROWS,
+            Self::Failed(e) => e.exit_code(),
+        }
+    }
+}
+
+/// Runs every job of a batch manifest over one connection, then exits after printing a summary
+///
+/// Every job is planned before connecting, so problems such as a missing query file show up
+/// before any job runs. The batch exits with the status of the first job that failed or found
+/// no rows, or 0 when every job succeeded.
+fn run_batch(cli: &Cli, args: &BatchArgs) -> ! {
+    let jobs = match load_manifest(&args.manifest) {
+        Ok(jobs) => jobs,
+        Err(e) => exit_with_error(e, Some("Batch manifest loading failed")),
+    };
+    let keep_going = args.keep_going && !args.fail_fast;
+
+    let mut statuses = Vec::with_capacity(jobs.len());
+    let mut planned = Vec::with_capacity(jobs.len());
+    for job in &jobs {
+        let plan = job
+            .cli(cli)
+            .map_err(|e| RunError::new(e, Some("Invalid batch job")))
+            .and_then(|job_cli| plan_run(&job_cli).map(|plan| (job_cli, plan)));
+        match plan {
+            Ok(plan) => {
+                statuses.push(JobStatus::Skipped);
+                planned.push(Some(plan));
+            },
+            Err(e) => {
+                statuses.push(JobStatus::Failed(e));
+                planned.push(None);
+            },
+        }
+    }
+
+    let mut timings = vec![None; jobs.len()];
+    if keep_going || !statuses.iter().any(|status| matches!(status, JobStatus::Failed(_))) {
+        let database_url = match resolve_database_url(cli) {
+            Ok(url) => url,
+            Err(e) => exit_with_error(e, Some("Database URL resolution failed")),
+        };
+        let pool = match create_database_connection(&database_url, cli) {
+            Ok(pool) => pool,
+            Err(e) => exit_with_error(anyhow::anyhow!("Database connection pool creation failed: {}", e), None),
+        };
+        let retry = retry_policy(cli, &database_url);
+        let mut conn = match retry.run(Operation::Connect, || pool.get_conn()) {
+            Ok(conn) => conn,
+            Err(e) => exit_with_error(anyhow::anyhow!("Database connection failed: {}", e), None),
+        };
+
+        for (index, plan) in planned.iter().enumerate() {
+            let Some((job_cli, plan)) = plan else {
+                continue;
+            };
             if cli.verbose > 0 && !cli.quiet {
-                println!("No records found in database.");
+                eprintln!("Running job {}...", jobs[index].name);
+            }
+            let started = Instant::now();
+            let status = match execute_run(job_cli, plan, &pool, &mut conn, &retry, std::convert::identity) {
+                Ok(outcome) => JobStatus::Finished(outcome),
+                Err(e) => {
+                    // Remove what the failed job left behind, and replace a connection it lost
+                    gold_digger::output::remove_partial_files();
+                    if conn.as_mut().ping().is_err()
+                        && let Ok(fresh) = retry.run(Operation::Connect, || pool.get_conn())
+                    {
+                        conn = fresh;
+                    }
+                    JobStatus::Failed(e)
+                },
+            };
+            reset_session(job_cli, &mut conn);
+            timings[index] = Some(started.elapsed());
+            let stop = !keep_going && status.exit_code() != EXIT_SUCCESS;
+            statuses[index] = status;
+            if stop {
+                break;
             }
-            exit_no_rows(Some("No records found in database"));
         }
-    } else {
-        let rows = match rows_to_strings(result) {
-            Ok(rows) => rows,
-            Err(e) => exit_with_error(
-                e.context("Failed to convert database rows to string format"),
-                Some("Row conversion failed"),
-            ),
-        };
-        let output = match File::create(&output_file) {
-            Ok(output) => output,
-            Err(e) => exit_with_error(anyhow::anyhow!("Failed to create output file: {}", e), None),
+    }
+
+    print_batch_summary(cli, &jobs, &statuses, &timings);
+    let exit_code = statuses
+        .iter()
+        .map(JobStatus::exit_code)
+        .find(|&code| code != EXIT_SUCCESS)
+        .unwrap_or(EXIT_SUCCESS);
+    std::process::exit(exit_code);
+}
+
+/// Prints one line per job and the totals to stderr
+///
+/// With --quiet only failed jobs are listed.
+fn print_batch_summary(cli: &Cli, jobs: &[BatchJob], statuses: &[JobStatus], timings: &[Option<Duration>]) {
+    let count = |wanted: fn(&JobStatus) -> bool| statuses.iter().filter(|status| wanted(status)).count();
+    let failed = count(|status| matches!(status, JobStatus::Failed(_)));
+    if cli.quiet && failed == 0 {
+        return;
+    }
+
+    for ((job, status), timing) in jobs.iter().zip(statuses).zip(timings) {
+        let elapsed = timing.map_or_else(String::new, |elapsed| format!(" in {:.2}s", elapsed.as_secs_f64()));
+        let line = match status {
+            JobStatus::Finished(RunOutcome::Written {
+                records,
+                result_sets,
+                written_to,
+            }) => {
+                let sets = if *result_sets > 1 {
+                    format!(" from {} result sets", result_sets)
+                } else {
+                    String::new()
+                };
+                format!("ok       {}: {} records{} to {}{}", job.name, records, sets, written_to, elapsed)
+            },
+            JobStatus::Finished(RunOutcome::NoRows) => {
+                format!("no rows  {}: no records found (exit {}){}", job.name, EXIT_NO_ROWS, elapsed)
+            },
+            JobStatus::Failed(e) => format!("failed   {}: {} (exit {}){}", job.name, e, e.exit_code(), elapsed),
+            JobStatus::Skipped => format!("skipped  {}", job.name),
         };
-        if let Err(e) = write_output(rows, output, output_file.as_path(), &cli) {
-            exit_with_error(e, Some("Output writing failed"));
+        if !cli.quiet || matches!(status, JobStatus::Failed(_)) {
+            eprintln!("  {}", line);
         }
     }
+    eprintln!(
+        "Batch finished: {} jobs, {} succeeded, {} without rows, {} failed, {} skipped",
+        jobs.len(),
+        count(|status| matches!(status, JobStatus::Finished(RunOutcome::Written { .. }))),
+        count(|status| matches!(status, JobStatus::Finished(RunOutcome::NoRows))),
+        failed,
+        count(|status| matches!(status, JobStatus::Skipped)),
+    );
+}
 
-    exit_success(None);
+/// Maps a MySQL driver error to a contextual, redacted error message
+fn describe_query_error(e: &mysql::Error, cli: &Cli) -> anyhow::Error {
+    // Structured error matching on mysql::Error variants
+    let (context, should_show_details) = match e {
+        mysql::Error::MySqlError(mysql_err) => {
+            // Map known MySQL error codes to contextual messages
+            let context = match mysql_err.code {
+                1064 => "SQL syntax error in query",                   // ER_PARSE_ERROR
+                1146 => "Table does not exist",                        // ER_NO_SUCH_TABLE
+                1054 => "Column does not exist or is ambiguous",       // ER_BAD_FIELD_ERROR
+                1045 => "Access denied - invalid credentials",         // ER_ACCESS_DENIED_ERROR
+                1044 => "Access denied to database",                   // ER_DBACCESS_DENIED_ERROR
+                1142 => "Insufficient privileges for query execution", // ER_TABLEACCESS_DENIED_ERROR
+                1143 => "Insufficient column privileges",              // ER_COLUMNACCESS_DENIED_ERROR
+                1049 => "Unknown database",                            // ER_BAD_DB_ERROR
+                2002 => "Connection failed - server not reachable",    // CR_CONNECTION_ERROR
+                2003 => "Connection failed - server not responding",   // CR_CONN_HOST_ERROR
+                2006 => "Connection lost - server has gone away",      // CR_SERVER_GONE_ERROR
+                2013 => "Connection lost during query",                // CR_SERVER_LOST
+                3024 => "Query timed out - server time limit reached", // ER_QUERY_TIMEOUT
+                1969 => "Query timed out - server time limit reached", // ER_STATEMENT_TIMEOUT (MariaDB)
+                _ => "Query execution failed",
+            };
+            (context, true)
+        },
+        mysql::Error::IoError(_) => ("Network I/O error during query execution", false),
+        mysql::Error::UrlError(_) => ("Invalid database URL format", false),
+        mysql::Error::DriverError(_) => ("Database driver error", false),
+        _ => ("Query execution failed", false),
+    };
+
+    // Create error message with appropriate level of detail
+    if cli.verbose > 0 && should_show_details {
+        anyhow::anyhow!("{}: {}", context, redact_sql_error(&e.to_string()))
+    } else {
+        anyhow::anyhow!("{}", context)
+    }
 }
 
 /// Creates a database connection pool with rustls-only TLS configuration from CLI
@@ -173,45 +634,53 @@ fn create_database_connection(database_url: &str, cli: &Cli) -> Result<Pool> {
     };
 
     // Use rustls-only TLS connection creation with enhanced error handling
-    create_tls_connection(database_url, tls_config, cli.verbose > 0).map_err(|tls_error| {
-        // Convert TLS errors to anyhow errors with appropriate context
-        match &tls_error {
-            gold_digger::tls::TlsError::CertificateValidationFailed { .. }
-            | gold_digger::tls::TlsError::CertificateTimeInvalid { .. }
-            | gold_digger::tls::TlsError::InvalidSignature { .. }
-            | gold_digger::tls::TlsError::UnknownCertificateAuthority { .. }
-            | gold_digger::tls::TlsError::InvalidCertificatePurpose { .. }
-            | gold_digger::tls::TlsError::CertificateChainInvalid { .. }
-            | gold_digger::tls::TlsError::CertificateRevoked { .. } => {
-                // Certificate validation errors - suggest appropriate CLI flag
-                if let Some(suggestion) = tls_error.suggest_cli_flag() {
-                    anyhow::anyhow!("{}. Suggestion: {}", tls_error, suggestion)
-                } else {
+    let retry = retry_policy(cli, database_url);
+    create_tls_connection_with_retry(database_url, tls_config, cli.verbose > 0, cli.connect_timeout, &retry).map_err(
+        |tls_error| {
+            // Convert TLS errors to anyhow errors with appropriate context
+            match &tls_error {
+                gold_digger::tls::TlsError::CertificateValidationFailed { .. }
+                | gold_digger::tls::TlsError::CertificateTimeInvalid { .. }
+                | gold_digger::tls::TlsError::InvalidSignature { .. }
+                | gold_digger::tls::TlsError::UnknownCertificateAuthority { .. }
+                | gold_digger::tls::TlsError::InvalidCertificatePurpose { .. }
+                | gold_digger::tls::TlsError::CertificateChainInvalid { .. }
+                | gold_digger::tls::TlsError::CertificateRevoked { .. } => {
+                    // Certificate validation errors - suggest appropriate CLI flag
+                    if let Some(suggestion) = tls_error.suggest_cli_flag() {
+                        anyhow::anyhow!("{}. Suggestion: {}", tls_error, suggestion)
+                    } else {
+                        anyhow::anyhow!("{}", tls_error)
+                    }
+                },
+                gold_digger::tls::TlsError::HostnameVerificationFailed { .. } => {
+                    // Hostname verification errors - suggest skip hostname flag
+                    anyhow::anyhow!(
+                        "{}. Suggestion: {}",
+                        tls_error,
+                        tls_error
+                            .suggest_cli_flag()
+                            .unwrap_or("--insecure-skip-hostname-verify")
+                    )
+                },
+                gold_digger::tls::TlsError::CaFileNotFound { .. }
+                | gold_digger::tls::TlsError::InvalidCaFormat { .. }
+                | gold_digger::tls::TlsError::MutuallyExclusiveFlags { .. } => {
+                    // Client configuration errors - no additional context needed
                     anyhow::anyhow!("{}", tls_error)
-                }
-            },
-            gold_digger::tls::TlsError::HostnameVerificationFailed { .. } => {
-                // Hostname verification errors - suggest skip hostname flag
-                anyhow::anyhow!(
-                    "{}. Suggestion: {}",
-                    tls_error,
-                    tls_error
-                        .suggest_cli_flag()
-                        .unwrap_or("--insecure-skip-hostname-verify")
-                )
-            },
-            gold_digger::tls::TlsError::CaFileNotFound { .. }
-            | gold_digger::tls::TlsError::InvalidCaFormat { .. }
-            | gold_digger::tls::TlsError::MutuallyExclusiveFlags { .. } => {
-                // Client configuration errors - no additional context needed
-                anyhow::anyhow!("{}", tls_error)
-            },
-            _ => {
-                // Other TLS errors (handshake, connection, server issues)
-                anyhow::anyhow!("Database connection failed: {}", tls_error)
-            },
-        }
-    })
+                },
+                _ => {
+                    // Other TLS errors (handshake, connection, server issues)
+                    anyhow::anyhow!("Database connection failed: {}", tls_error)
+                },
+            }
+        },
+    )
+}
+
+/// Returns how transient connection and query failures are retried
+fn retry_policy(cli: &Cli, database_url: &str) -> RetryPolicy {
+    RetryPolicy::new(cli.retries, cli.retry_backoff, database_url, cli.quiet)
 }
 
 /// Resolves the database URL from CLI arguments or environment variables
@@ -249,27 +718,393 @@ fn resolve_output_file(cli: &Cli) -> Result<PathBuf> {
     }
 }
 
-/// Writes output in the specified format
-fn write_output(rows: Vec<Vec<String>>, output: File, output_file: &std::path::Path, cli: &Cli) -> Result<()> {
-    let format = if let Some(format) = &cli.format {
-        format.clone()
+/// Expands placeholders in the output path, then creates its parent directory with `--create-dirs`
+///
+/// Paths without braces are used as given, so they need not be valid UTF-8.
+fn prepare_output_file(cli: &Cli, output_file: PathBuf, params: &QueryParams, now: DateTime<Utc>) -> Result<PathBuf> {
+    let output_file = if is_template(&output_file) {
+        let values = TemplateValues {
+            now,
+            timezone: cli.output_timezone.unwrap_or_default(),
+            query_file: cli.query_file.as_deref(),
+            params,
+        };
+        PathBuf::from(expand_template(&output_file.to_string_lossy(), &values)?)
     } else {
-        OutputFormat::from_extension(output_file)
+        output_file
     };
 
-    match format {
-        #[cfg(feature = "csv")]
-        OutputFormat::Csv => gold_digger::csv::write(rows, output)?,
-        #[cfg(feature = "json")]
-        OutputFormat::Json => gold_digger::json::write_with_pretty(rows, output, cli.pretty)?,
-        OutputFormat::Tsv => gold_digger::tab::write(rows, output)?,
-        #[cfg(not(feature = "csv"))]
-        OutputFormat::Csv => anyhow::bail!("CSV support not compiled in"),
-        #[cfg(not(feature = "json"))]
-        OutputFormat::Json => anyhow::bail!("JSON support not compiled in"),
+    if cli.create_dirs
+        && !gold_digger::is_stdout(&output_file)
+        && let Some(parent) = output_file.parent().filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            anyhow::anyhow!("Failed to create directory for output file {}: {}", output_file.display(), e)
+        })?;
+    }
+    Ok(output_file)
+}
+
+/// Resolves the output format from `--format` or the output file extension
+///
+/// Standard output has no extension, so `--format` is required when the output is `-`.
+fn resolve_output_format(cli: &Cli, output_file: &Path) -> Result<OutputFormat> {
+    match cli.format {
+        Some(format) => Ok(format),
+        None if gold_digger::is_stdout(output_file) => {
+            anyhow::bail!("Missing --format: the output format cannot be inferred when writing to stdout (-o -)")
+        },
+        None => Ok(OutputFormat::from_extension(output_file)),
     }
+}
 
-    Ok(())
+/// Resolves output compression from `--compress` or a trailing `.gz`/`.zst` extension
+fn resolve_compression(cli: &Cli, output_file: &Path) -> Result<Compression> {
+    let compression = cli.compress.unwrap_or_else(|| Compression::from_extension(output_file));
+    check_compression(compression, cli.compression_level)?;
+    Ok(compression)
+}
+
+/// Resolves `--split-rows` and `--split-bytes`, checking that the output can be split
+fn resolve_split_limits(cli: &Cli, output_file: &Path, format: OutputFormat) -> Result<SplitLimits> {
+    let limits = SplitLimits {
+        rows: cli.split_rows,
+        bytes: cli.split_bytes,
+    };
+    if limits.is_enabled() {
+        if gold_digger::is_stdout(output_file) {
+            anyhow::bail!("Invalid --split-rows/--split-bytes: output written to stdout cannot be split into parts");
+        }
+        if !gold_digger::formats::SPLITTABLE_FORMATS.contains(&format) {
+            anyhow::bail!(
+                "Invalid --split-rows/--split-bytes: {} output cannot be split into parts (supported: csv, tsv, json)",
+                format.as_str()
+            );
+        }
+    }
+    Ok(limits)
+}
+
+/// How results are laid out on disk
+enum OutputLayout {
+    /// A single file, or stdout
+    Single,
+    /// Numbered parts of bounded size
+    Split(SplitLimits),
+    /// One file per distinct value of the partition columns
+    Partitioned { columns: Vec<String>, max_open: usize },
+}
+
+/// Resolves `--partition-by` or the split limits into an output layout
+fn resolve_output_layout(cli: &Cli, output_file: &Path, format: OutputFormat) -> Result<OutputLayout> {
+    if cli.partition_by.is_empty() {
+        let limits = resolve_split_limits(cli, output_file, format)?;
+        return Ok(if limits.is_enabled() {
+            OutputLayout::Split(limits)
+        } else {
+            OutputLayout::Single
+        });
+    }
+
+    if gold_digger::is_stdout(output_file) {
+        anyhow::bail!("Invalid --partition-by: output written to stdout cannot be partitioned");
+    }
+    if cli.partition_by.iter().any(|column| column.trim().is_empty()) {
+        anyhow::bail!("Invalid --partition-by: column names must not be empty");
+    }
+    Ok(OutputLayout::Partitioned {
+        columns: cli.partition_by.clone(),
+        max_open: cli
+            .max_open_partitions
+            .map_or(DEFAULT_MAX_OPEN_PARTITIONS, |max_open| usize::try_from(max_open).unwrap_or(usize::MAX)),
+    })
+}
+
+/// Resolves `--result-sets`, checking that combined output is possible
+fn resolve_result_set_mode(cli: &Cli, format: OutputFormat, layout: &OutputLayout) -> Result<ResultSetMode> {
+    let mode = cli.result_sets.unwrap_or_default();
+    if mode == ResultSetMode::Combined {
+        if format != OutputFormat::Json {
+            anyhow::bail!(
+                "Invalid --result-sets combined: {} output cannot hold several result sets (supported: json)",
+                format.as_str()
+            );
+        }
+        if !matches!(layout, OutputLayout::Single) {
+            anyhow::bail!(
+                "Invalid --result-sets combined: cannot be used with --split-rows, --split-bytes or --partition-by"
+            );
+        }
+    }
+    Ok(mode)
+}
+
+/// Result sets written so far, committed together once the query has finished
+struct ResultSetOutputs<'a> {
+    cli: &'a Cli,
+    output_file: &'a Path,
+    format: OutputFormat,
+    compression: Compression,
+    layout: &'a OutputLayout,
+    /// The single document holding every result set with `--result-sets combined`
+    combined: Option<CombinedOutput>,
+    /// One output per result set otherwise, with the path it is written to
+    outputs: Vec<(PendingOutput, PathBuf)>,
+    /// Result sets written so far
+    written: usize,
+    /// Columns of empty result sets held back until a row arrives, so a query without rows
+    /// writes nothing
+    empty_sets: Vec<Vec<Column>>,
+    /// Records written so far
+    record_count: u64,
+}
+
+impl ResultSetOutputs<'_> {
+    /// Writes every result set of a query
+    fn export<T: Protocol>(&mut self, result: &mut QueryResult<'_, '_, '_, T>) -> RunResult<()> {
+        while let Some(mut set) = result.iter() {
+            let columns = set.columns().as_ref().to_vec();
+            if columns.is_empty() {
+                // Statements such as SET, and the status at the end of a CALL, return no result set
+                if let Some(Err(e)) = set.next() {
+                    return Err(RunError::query(&e, self.cli));
+                }
+                continue;
+            }
+
+            // Rows are converted and handed to the writer one at a time so memory stays flat
+            let mut rows = RowStream::new(set.by_ref());
+            if rows.is_empty() && !self.cli.allow_empty && self.record_count == 0 {
+                self.empty_sets.push(columns);
+                continue;
+            }
+            // Empty result sets keep their header, so every file of a query has its schema
+            for empty_columns in std::mem::take(&mut self.empty_sets) {
+                self.write(&empty_columns, std::iter::empty())?;
+            }
+            self.write(&columns, &mut rows)?;
+            self.record_count += finish_rows(rows, self.cli)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the next result set
+    fn write<R>(&mut self, columns: &[Column], rows: R) -> RunResult<()>
+    where
+        R: Iterator<Item = Vec<Value>>,
+    {
+        self.written += 1;
+        if let Some(combined) = self.combined.as_mut() {
+            return combined.write(columns, rows).map_err(RunError::output);
+        }
+
+        let path = self.next_path()?;
+        if self.cli.verbose > 0 && !self.cli.quiet && !columns.is_empty() {
+            eprintln!("Streaming records to {}...", describe_output(&path));
+        }
+        let output = write_results(columns, rows, self.format, &path, self.compression, self.layout, self.cli)?;
+        self.outputs.push((output, path));
+        Ok(())
+    }
+
+    /// Returns the path for the result set being written
+    ///
+    /// A single result set is written to the output path itself. When a second one arrives,
+    /// the first output is moved to `out.1.csv` before it is committed and this one goes to
+    /// `out.2.csv`. Result sets written to stdout follow one another.
+    fn next_path(&mut self) -> RunResult<PathBuf> {
+        if self.written == 1 || gold_digger::is_stdout(self.output_file) {
+            return Ok(self.output_file.to_path_buf());
+        }
+        if let [(first, path)] = self.outputs.as_mut_slice() {
+            match first {
+                PendingOutput::Single(destination) => {
+                    *path = result_set_path(self.output_file, 1);
+                    destination.set_target(path);
+                },
+                PendingOutput::Split(_) | PendingOutput::Partitioned(_) => {
+                    return Err(RunError::new(
+                        anyhow::anyhow!(
+                            "Invalid output layout: the query returned more than one result set, which --split-rows, --split-bytes and --partition-by do not support"
+                        ),
+                        None,
+                    ));
+                },
+            }
+        }
+        Ok(result_set_path(self.output_file, self.written))
+    }
+
+    /// Commits every output, returning a description of where they were written
+    fn commit(self) -> RunResult<String> {
+        let mut outputs = self.outputs;
+        if let Some(combined) = self.combined {
+            let destination = combined.finish().map_err(RunError::output)?;
+            outputs.push((PendingOutput::Single(destination), self.output_file.to_path_buf()));
+        }
+        let mut written_to = Vec::with_capacity(outputs.len());
+        for (output, path) in outputs {
+            written_to.push(output.commit(&path).map_err(RunError::output)?);
+        }
+        // Result sets written to stdout all share one description
+        written_to.dedup();
+        Ok(written_to.join(", "))
+    }
+}
+
+/// Finishes a row stream, returning its record count or the error that ended it
+///
+/// The output only replaces the target once every row has converted successfully.
+fn finish_rows<I>(rows: RowStream<I>, cli: &Cli) -> RunResult<u64>
+where
+    I: Iterator<Item = mysql::Result<mysql::Row>>,
+{
+    rows.finish().map_err(|e| match e.downcast_ref::<mysql::Error>() {
+        Some(mysql_err) => RunError::query(mysql_err, cli),
+        None => {
+            RunError::new(e.context("Failed to convert database rows to string format"), Some("Row conversion failed"))
+        },
+    })
+}
+
+/// Opens the output sink: stdout when the path is `-`, otherwise a temporary file that is
+/// renamed into place on success, compressed on the fly when requested
+fn open_output(
+    output_file: &Path,
+    compression: Compression,
+    level: Option<i32>,
+) -> Result<CompressedWriter<Destination>> {
+    CompressedWriter::new(Destination::open(output_file)?, compression, level)
+}
+
+/// Describes the output destination for diagnostics
+fn describe_output(output_file: &Path) -> String {
+    if gold_digger::is_stdout(output_file) {
+        "stdout".to_string()
+    } else {
+        output_file.display().to_string()
+    }
+}
+
+/// Output that has been written in full but not committed yet
+enum PendingOutput {
+    /// A single file or stdout
+    Single(Destination),
+    /// Numbered parts whose index has not been written
+    Split(SplitOutput),
+    /// Partition files, some of them not yet renamed into place
+    Partitioned(PartitionedOutput),
+}
+
+impl PendingOutput {
+    /// Commits the output, returning a description of where it was written
+    fn commit(self, output_file: &Path) -> Result<String> {
+        match self {
+            Self::Single(destination) => {
+                destination.commit()?;
+                Ok(describe_output(output_file))
+            },
+            Self::Split(split) => {
+                let parts = split.parts().len();
+                let index = split.finish()?;
+                Ok(format!("{} parts indexed in {}", parts, index.display()))
+            },
+            Self::Partitioned(partitioned) => {
+                let files = partitioned.file_count();
+                partitioned.finish()?;
+                let directory = match output_file.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent,
+                    _ => Path::new("."),
+                };
+                Ok(format!("{} partition files under {}", files, directory.display()))
+            },
+        }
+    }
+}
+
+/// Writes the results in the given layout
+fn write_results<R>(
+    columns: &[Column],
+    rows: R,
+    format: OutputFormat,
+    output_file: &Path,
+    compression: Compression,
+    layout: &OutputLayout,
+    cli: &Cli,
+) -> RunResult<PendingOutput>
+where
+    R: Iterator<Item = Vec<Value>>,
+{
+    let level = cli.compression_level;
+    let result = match layout {
+        OutputLayout::Single => {
+            let output = open_output(output_file, compression, level).map_err(|e| RunError::new(e, None))?;
+            write_output(columns, rows, output, format, output_file, cli).map(PendingOutput::Single)
+        },
+        OutputLayout::Split(limits) => {
+            let mut split = SplitOutput::new(output_file, format, compression, level, *limits);
+            split
+                .write(columns, rows, &writer_options(cli, output_file))
+                .map(|()| PendingOutput::Split(split))
+        },
+        OutputLayout::Partitioned {
+            columns: partition_by,
+            max_open,
+        } => {
+            let mut partitioned =
+                PartitionedOutput::new(output_file, format, compression, level, partition_by.clone(), *max_open);
+            partitioned
+                .write(columns, rows, &writer_options(cli, output_file))
+                .map(|()| PendingOutput::Partitioned(partitioned))
+        },
+    };
+    result.map_err(RunError::output)
+}
+
+/// Builds the writer options from the CLI
+///
+/// The SQL table name defaults to the output file name without its extensions.
+fn writer_options(cli: &Cli, output_file: &Path) -> WriterOptions {
+    WriterOptions {
+        pretty: cli.pretty,
+        null_marker: cli.null_marker.clone().unwrap_or_default(),
+        row_group_size: cli.row_group_size,
+        sql_table: cli.sql_table.clone().or_else(|| {
+            if gold_digger::is_stdout(output_file) {
+                return None;
+            }
+            strip_compression_extension(output_file)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(str::to_string)
+        }),
+        sql_batch_size: cli.sql_batch_size,
+        width_limits: cli.max_width.clone(),
+    }
+}
+
+/// Writes output in the specified format, streaming rows as they are produced
+///
+/// Returns the destination with all data written but not yet committed, so the caller can
+/// still discard it if the row stream ends in an error.
+fn write_output<R>(
+    columns: &[Column],
+    rows: R,
+    mut output: CompressedWriter<Destination>,
+    format: OutputFormat,
+    output_file: &Path,
+    cli: &Cli,
+) -> Result<Destination>
+where
+    R: Iterator<Item = Vec<Value>>,
+{
+    let mut writer = create_writer(format, Box::new(&mut output), &writer_options(cli, output_file))?;
+    writer.write_columns(columns)?;
+    for row in rows {
+        writer.write_values(&row)?;
+    }
+    writer.finalize()?;
+    output.finish()
 }
 
 /// Generates shell completion scripts
@@ -307,17 +1142,45 @@ fn dump_configuration(cli: &Cli) -> Result<()> {
 
     let config = json!({
         "database_url": "***REDACTED***", // Always redact database URLs
+        "profile": cli.profile,
+        "config": cli.config.as_ref().map(|p| p.display().to_string()),
         "query": redacted_query,
         "query_file": cli.query_file.as_ref().map(|p| p.display().to_string()),
         "output": cli.output.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| env::var("OUTPUT_FILE").unwrap_or_default()),
         "format": cli.format.as_ref().map(|f| f.as_str()),
+        "compress": cli.compress.as_ref().map(|c| c.as_str()),
+        "compression_level": cli.compression_level,
         "verbose": cli.verbose,
         "quiet": cli.quiet,
         "pretty": cli.pretty,
+        "null_marker": cli.null_marker,
+        "row_group_size": cli.row_group_size,
+        "sql_table": cli.sql_table,
+        "sql_batch_size": cli.sql_batch_size,
+        "split_rows": cli.split_rows,
+        "split_bytes": cli.split_bytes,
+        "partition_by": cli.partition_by,
+        "max_open_partitions": cli.max_open_partitions,
+        "result_sets": cli.result_sets.as_ref().map(|m| m.as_str()),
+        "params": cli.params.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        "params_file": cli.params_file.as_ref().map(|p| p.display().to_string()),
+        "output_timezone": cli.output_timezone.as_ref().map(|z| z.as_str()),
+        "create_dirs": cli.create_dirs,
+        "max_width": cli.max_width.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        "query_timeout": cli.query_timeout.map(format_duration),
+        "read_only": !cli.allow_writes,
+        "connect_timeout": cli.connect_timeout.map(format_duration),
+        "retries": cli.retries,
+        "retry_backoff": cli.retry_backoff.map(format_duration),
         "allow_empty": cli.allow_empty,
         "features": {
             "json": cfg!(feature = "json"),
             "csv": cfg!(feature = "csv"),
+            "parquet": cfg!(feature = "parquet"),
+            "arrow": cfg!(feature = "arrow"),
+            "xlsx": cfg!(feature = "xlsx"),
+            "gzip": cfg!(feature = "gzip"),
+            "zstd": cfg!(feature = "zstd"),
             "verbose": cfg!(feature = "verbose"),
             "additional_mysql_types": cfg!(feature = "additional_mysql_types"),
             "tls": true  // TLS is always available (rustls-only implementation)
@@ -520,6 +1383,109 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_resolve_output_format() {
+        let cli = build_test_cli();
+        assert_eq!(resolve_output_format(&cli, Path::new("test.json")).unwrap(), OutputFormat::Json);
+
+        let err = resolve_output_format(&cli, Path::new("-")).unwrap_err();
+        assert!(err.to_string().contains("Missing --format"));
+
+        let cli = Cli::parse_from(["gold_digger", "--output", "-", "--format", "ndjson"]);
+        assert_eq!(resolve_output_format(&cli, Path::new("-")).unwrap(), OutputFormat::Ndjson);
+    }
+
+    #[test]
+    fn test_resolve_compression() {
+        let cli = build_test_cli();
+        assert_eq!(resolve_compression(&cli, Path::new("test.json")).unwrap(), Compression::None);
+        assert_eq!(OutputFormat::from_extension(Path::new("test.json.zst")), OutputFormat::Json);
+
+        let cli = Cli::parse_from(["gold_digger", "--output", "-", "--compression-level", "3"]);
+        let err = resolve_compression(&cli, Path::new("-")).unwrap_err();
+        assert!(err.to_string().contains("Invalid compression level"));
+    }
+
+    #[test]
+    fn test_resolve_split_limits() {
+        let cli = Cli::parse_from(["gold_digger", "--split-rows", "1000", "--split-bytes", "100M"]);
+        let limits = resolve_split_limits(&cli, Path::new("out.csv.gz"), OutputFormat::Csv).unwrap();
+        assert_eq!(limits.rows, Some(1000));
+        assert_eq!(limits.bytes, Some(100 * 1024 * 1024));
+
+        let err = resolve_split_limits(&cli, Path::new("-"), OutputFormat::Csv).unwrap_err();
+        assert!(err.to_string().contains("stdout"));
+        let err = resolve_split_limits(&cli, Path::new("out.parquet"), OutputFormat::Parquet).unwrap_err();
+        assert!(err.to_string().contains("parquet output cannot be split"));
+
+        assert!(Cli::try_parse_from(["gold_digger", "--split-rows", "0"]).is_err());
+        assert!(Cli::try_parse_from(["gold_digger", "--split-bytes", "lots"]).is_err());
+        let cli = build_test_cli();
+        assert!(
+            !resolve_split_limits(&cli, Path::new("-"), OutputFormat::Sql)
+                .unwrap()
+                .is_enabled()
+        );
+    }
+
+    #[test]
+    fn test_resolve_output_layout() {
+        let cli = build_test_cli();
+        assert!(matches!(
+            resolve_output_layout(&cli, Path::new("test.json"), OutputFormat::Json).unwrap(),
+            OutputLayout::Single
+        ));
+
+        let cli = Cli::parse_from([
+            "gold_digger",
+            "--partition-by",
+            "region,day",
+            "--max-open-partitions",
+            "8",
+        ]);
+        match resolve_output_layout(&cli, Path::new("out/part.parquet"), OutputFormat::Parquet).unwrap() {
+            OutputLayout::Partitioned { columns, max_open } => {
+                assert_eq!(columns, ["region", "day"]);
+                assert_eq!(max_open, 8);
+            },
+            _ => panic!("expected a partitioned layout"),
+        }
+        let err = resolve_output_layout(&cli, Path::new("-"), OutputFormat::Csv)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("cannot be partitioned"));
+
+        assert!(Cli::try_parse_from(["gold_digger", "--partition-by", "day", "--split-rows", "10"]).is_err());
+        assert!(Cli::try_parse_from(["gold_digger", "--max-open-partitions", "8"]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_result_set_mode() {
+        let cli = build_test_cli();
+        assert_eq!(
+            resolve_result_set_mode(&cli, OutputFormat::Csv, &OutputLayout::Single).unwrap(),
+            ResultSetMode::Numbered
+        );
+
+        let cli = Cli::parse_from(["gold_digger", "--result-sets", "combined"]);
+        assert_eq!(
+            resolve_result_set_mode(&cli, OutputFormat::Json, &OutputLayout::Single).unwrap(),
+            ResultSetMode::Combined
+        );
+        let err = resolve_result_set_mode(&cli, OutputFormat::Csv, &OutputLayout::Single)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("csv output cannot hold several result sets"));
+        let layout = OutputLayout::Split(SplitLimits {
+            rows: Some(10),
+            bytes: None,
+        });
+        let err = resolve_result_set_mode(&cli, OutputFormat::Json, &layout)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("cannot be used with --split-rows"));
+    }
+
     #[test]
     fn test_dump_configuration() -> anyhow::Result<()> {
         let cli = build_test_cli();
@@ -0,0 +1,153 @@
+use std::io::{BufWriter, Write};
+
+use anyhow::Result;
+use mysql::{Column, Value};
+
+use crate::types::column_kinds;
+use crate::{FormatWriter, OUTPUT_BUFFER_CAPACITY, mysql_value_to_string};
+
+/// GitHub-flavored Markdown table writer that implements the FormatWriter trait
+///
+/// Writes a pipe table with the column names as the header row. Pipes in cell text are
+/// escaped as `\|` and line breaks become `<br>` so that every row stays on one line.
+/// Numeric columns are right-aligned when column metadata is available.
+pub struct MarkdownWriter<W: Write> {
+    writer: BufWriter<W>,
+    null_marker: String,
+    right_align: Vec<bool>,
+}
+
+impl<W: Write> MarkdownWriter<W> {
+    /// Creates a new MarkdownWriter over the specified writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::with_capacity(OUTPUT_BUFFER_CAPACITY, writer),
+            null_marker: String::new(),
+            right_align: Vec::new(),
+        }
+    }
+
+    /// Sets the string written for SQL NULL values (empty by default)
+    pub fn with_null_marker(mut self, null_marker: impl Into<String>) -> Self {
+        self.null_marker = null_marker.into();
+        self
+    }
+
+    /// Writes one table row from unescaped cell text
+    fn write_cells<'a>(&mut self, cells: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        let mut line = String::from("|");
+        for cell in cells {
+            line.push(' ');
+            push_escaped(&mut line, cell);
+            line.push_str(" |");
+        }
+        writeln!(self.writer, "{}", line)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> FormatWriter for MarkdownWriter<W> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        // A pipe table needs at least one column
+        if columns.is_empty() {
+            return Ok(());
+        }
+        self.write_cells(columns.iter().map(String::as_str))?;
+
+        let mut delimiter = String::from("|");
+        for index in 0..columns.len() {
+            let right = self.right_align.get(index).copied().unwrap_or(false);
+            delimiter.push_str(if right { " ---: |" } else { " --- |" });
+        }
+        writeln!(self.writer, "{}", delimiter)?;
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        self.write_cells(row.iter().map(String::as_str))
+    }
+
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        self.right_align = column_kinds(columns)
+            .into_iter()
+            .map(|kind| kind.is_numeric())
+            .collect();
+        self.write_header(&crate::column_names(columns))
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        let mut row = Vec::with_capacity(values.len());
+        for value in values {
+            match value {
+                Value::NULL => row.push(self.null_marker.clone()),
+                _ => row.push(mysql_value_to_string(value)?),
+            }
+        }
+        self.write_row(&row)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Appends cell text with backslashes and pipes escaped and line breaks replaced by `<br>`
+fn push_escaped(out: &mut String, text: &str) {
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '|' => out.push_str("\\|"),
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                out.push_str("<br>");
+            },
+            '\n' => out.push_str("<br>"),
+            _ => out.push(ch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mysql::consts::ColumnType;
+
+    #[test]
+    fn test_markdown_table() {
+        let columns = [
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+                .with_name(b"note")
+                .with_character_set(33),
+        ];
+
+        let mut output = Vec::new();
+        let mut writer = MarkdownWriter::new(&mut output).with_null_marker("NULL");
+        writer.write_columns(&columns).unwrap();
+        writer
+            .write_values(&[Value::Int(1), Value::Bytes(b"a|b\r\nc\\d".to_vec())])
+            .unwrap();
+        writer.write_values(&[Value::Int(2), Value::NULL]).unwrap();
+        writer.finalize().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "| id | note |\n\
+             | ---: | --- |\n\
+             | 1 | a\\|b<br>c\\\\d |\n\
+             | 2 | NULL |\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_empty_header_writes_nothing() {
+        let mut output = Vec::new();
+        let mut writer = MarkdownWriter::new(&mut output);
+        writer.write_header(&[]).unwrap();
+        writer.finalize().unwrap();
+
+        assert!(output.is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+use std::io::{BufWriter, Write};
+
+use anyhow::Result;
+use mysql::{Column, Value};
+
+use crate::json::ObjectEncoder;
+use crate::{FormatWriter, OUTPUT_BUFFER_CAPACITY};
+
+/// Newline-delimited JSON (JSON Lines) writer that implements the FormatWriter trait
+///
+/// Each row is written as one compact JSON object followed by `\n`, using the same
+/// type-aware value conversion and key handling as [`crate::json::JsonWriter`]. There is
+/// no enclosing document, so rows are usable by consumers as soon as they are written.
+pub struct NdjsonWriter<W: Write> {
+    writer: BufWriter<W>,
+    encoder: ObjectEncoder,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    /// Creates a new NdjsonWriter over the specified writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::with_capacity(OUTPUT_BUFFER_CAPACITY, writer),
+            encoder: ObjectEncoder::default(),
+        }
+    }
+
+    /// Writes one JSON object line from already-encoded values
+    fn write_line(&mut self, encoded: Vec<String>) -> Result<()> {
+        self.encoder.write_object(&mut self.writer, encoded, false)?;
+        writeln!(self.writer)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> FormatWriter for NdjsonWriter<W> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        self.encoder.set_keys(columns);
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        let encoded = self.encoder.encode_strings(row);
+        self.write_line(encoded)
+    }
+
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        self.encoder.set_kinds(columns);
+        self.write_header(&crate::column_names(columns))
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        let encoded = self.encoder.encode_values(values)?;
+        self.write_line(encoded)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mysql::consts::{ColumnFlags, ColumnType};
+
+    #[test]
+    fn test_ndjson_one_object_per_line() {
+        let columns = [
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+                .with_name(b"zip")
+                .with_character_set(33),
+            Column::new(ColumnType::MYSQL_TYPE_LONGLONG)
+                .with_name(b"id")
+                .with_flags(ColumnFlags::UNSIGNED_FLAG),
+        ];
+
+        let mut output = Vec::new();
+        let mut writer = NdjsonWriter::new(&mut output);
+        writer.write_columns(&columns).unwrap();
+        writer
+            .write_values(&[
+                Value::Bytes(b"1".to_vec()),
+                Value::Bytes(b"00501".to_vec()),
+                Value::NULL,
+            ])
+            .unwrap();
+        writer
+            .write_values(&[Value::Bytes(b"2".to_vec()), Value::NULL, Value::UInt(7)])
+            .unwrap();
+        writer.finalize().unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, "{\"id\":1,\"zip\":\"00501\",\"id_2\":null}\n{\"id\":2,\"zip\":null,\"id_2\":7}\n");
+    }
+
+    #[test]
+    fn test_ndjson_empty_result_writes_nothing() {
+        let mut output = Vec::new();
+        let mut writer = NdjsonWriter::new(&mut output);
+        writer.write_header(&[]).unwrap();
+        writer.finalize().unwrap();
+
+        assert!(output.is_empty());
+    }
+}
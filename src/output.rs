@@ -0,0 +1,333 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+
+use crate::compression::{CompressedWriter, strip_compression_extension};
+
+/// Temporary files that have not been renamed into place yet, and committed parts of a
+/// split output whose index has not been written
+///
+/// `process::exit` skips destructors, so error exits remove these explicitly through
+/// [`remove_partial_files`].
+static PARTIAL_FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Serializes tests that create output files, since removing partial files affects all of them
+#[cfg(test)]
+pub(crate) static PARTIAL_FILES_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// Attempts at finding an unused temporary file name before giving up
+const MAX_TEMP_ATTEMPTS: u32 = 100;
+
+/// Removes every temporary output file that has not been committed
+///
+/// Called on error exits so that a failed run never leaves partial output behind. The
+/// target paths are untouched, so any previous complete output remains in place.
+pub fn remove_partial_files() {
+    let mut partial = PARTIAL_FILES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    for path in partial.drain(..) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+pub(crate) fn register_partial(path: &Path) {
+    let mut partial = PARTIAL_FILES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    partial.push(path.to_path_buf());
+}
+
+pub(crate) fn unregister_partial(path: &Path) {
+    let mut partial = PARTIAL_FILES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    partial.retain(|partial_path| partial_path != path);
+}
+
+/// Output file that only appears at its final path once it is complete
+///
+/// Data is written to a hidden temporary file in the target's directory. [`AtomicFile::commit`]
+/// fsyncs it and renames it over the target, so readers polling the target path see either
+/// the previous file or the complete new one, never a truncated file. If the file is dropped
+/// without being committed, or the process exits through
+/// [`crate::exit::exit_with_error`], the temporary file is removed.
+pub struct AtomicFile {
+    file: File,
+    temp_path: PathBuf,
+    target: PathBuf,
+    committed: bool,
+}
+
+impl AtomicFile {
+    /// Creates a temporary file next to `target`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target has no file name or the temporary file cannot be created.
+    pub fn create(target: &Path) -> Result<Self> {
+        let name = target
+            .file_name()
+            .with_context(|| format!("Invalid output file path: {}", target.display()))?;
+        let directory = target.parent().unwrap_or(Path::new(""));
+
+        for attempt in 0..MAX_TEMP_ATTEMPTS {
+            let mut temp_name = std::ffi::OsString::from(".");
+            temp_name.push(name);
+            temp_name.push(format!(".{}-{}.tmp", std::process::id(), attempt));
+            let temp_path = directory.join(temp_name);
+
+            match OpenOptions::new().write(true).create_new(true).open(&temp_path) {
+                Ok(file) => {
+                    register_partial(&temp_path);
+                    return Ok(Self {
+                        file,
+                        temp_path,
+                        target: target.to_path_buf(),
+                        committed: false,
+                    });
+                },
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        anyhow::bail!("Could not find an unused temporary file name next to {}", target.display())
+    }
+
+    /// Flushes and fsyncs the data, then atomically renames the file into place
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if syncing or renaming fails; the temporary file is removed.
+    pub fn commit(mut self) -> Result<()> {
+        self.file.flush()?;
+        self.file.sync_all()?;
+        fs::rename(&self.temp_path, &self.target)
+            .with_context(|| format!("Failed to move output file into place at {}", self.target.display()))?;
+        self.committed = true;
+        unregister_partial(&self.temp_path);
+        sync_directory(&self.target);
+        Ok(())
+    }
+}
+
+impl AtomicFile {
+    /// Changes the path the file is renamed to when it is committed
+    ///
+    /// The temporary file is only ever renamed, so the new target must be in the same
+    /// directory as the original one.
+    pub fn set_target(&mut self, target: &Path) {
+        self.target = target.to_path_buf();
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.temp_path);
+            unregister_partial(&self.temp_path);
+        }
+    }
+}
+
+/// Makes a completed rename durable by syncing the containing directory
+#[cfg(unix)]
+fn sync_directory(path: &Path) {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    // Not every filesystem supports syncing a directory; the rename has already happened
+    if let Ok(directory) = File::open(directory) {
+        let _ = directory.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) {}
+
+/// Destination that results are written to
+pub enum Destination {
+    /// Standard output, selected with the `-` path
+    Stdout(io::Stdout),
+    /// A file that is moved into place when the output is complete
+    File(AtomicFile),
+}
+
+impl Destination {
+    /// Opens stdout when the path is `-`, otherwise a temporary file for the path
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output file cannot be created.
+    pub fn open(path: &Path) -> Result<Self> {
+        if crate::is_stdout(path) {
+            Ok(Self::Stdout(io::stdout()))
+        } else {
+            let file = AtomicFile::create(path).map_err(|e| anyhow::anyhow!("Failed to create output file: {}", e))?;
+            Ok(Self::File(file))
+        }
+    }
+
+    /// Changes the path a file destination is renamed to; stdout is unaffected
+    ///
+    /// See [`AtomicFile::set_target`].
+    pub fn set_target(&mut self, target: &Path) {
+        if let Self::File(file) = self {
+            file.set_target(target);
+        }
+    }
+
+    /// Completes the output, renaming a file destination into place
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output cannot be flushed, synced, or renamed.
+    pub fn commit(self) -> Result<()> {
+        match self {
+            Self::Stdout(mut stdout) => stdout.flush()?,
+            Self::File(file) => file.commit()?,
+        }
+        Ok(())
+    }
+}
+
+impl Write for Destination {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout(stdout) => stdout.write(buf),
+            Self::File(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(stdout) => stdout.flush(),
+            Self::File(file) => file.flush(),
+        }
+    }
+}
+
+/// Output sink shared between a format writer and the output that owns it
+///
+/// Writers do not hand their sink back when they finish, so the owner keeps a second
+/// handle to finish compression and commit the file afterwards.
+#[derive(Clone)]
+pub(crate) struct SharedSink(Arc<Mutex<CompressedWriter<Destination>>>);
+
+impl SharedSink {
+    pub(crate) fn new(sink: CompressedWriter<Destination>) -> Self {
+        Self(Arc::new(Mutex::new(sink)))
+    }
+
+    fn lock(&self) -> io::Result<std::sync::MutexGuard<'_, CompressedWriter<Destination>>> {
+        self.0.lock().map_err(|_| io::Error::other("output sink lock poisoned"))
+    }
+
+    /// Returns the sink once the writer using it has been dropped
+    pub(crate) fn into_inner(self) -> Result<CompressedWriter<Destination>> {
+        let mutex = Arc::try_unwrap(self.0).map_err(|_| anyhow::anyhow!("Output sink is still in use"))?;
+        mutex
+            .into_inner()
+            .map_err(|_| anyhow::anyhow!("Output sink lock poisoned"))
+    }
+}
+
+impl Write for SharedSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock()?.flush()
+    }
+}
+
+/// Inserts `suffix` before the extensions of a file name, keeping any compression extension last
+///
+/// `out/data.csv.gz` with suffix `.1` becomes `out/data.1.csv.gz`.
+pub(crate) fn with_name_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let base = strip_compression_extension(path);
+    let mut name = std::ffi::OsString::from(base.file_stem().unwrap_or_default());
+    name.push(suffix);
+    for extension in [base.extension(), path.extension().filter(|_| base != path)]
+        .into_iter()
+        .flatten()
+    {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_files(directory: &Path) -> Vec<PathBuf> {
+        fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "tmp"))
+            .collect()
+    }
+
+    #[test]
+    fn test_commit_replaces_target() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let target = directory.path().join("out.csv");
+        fs::write(&target, "old\n").unwrap();
+
+        let mut file = AtomicFile::create(&target).unwrap();
+        file.write_all(b"new\n").unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "old\n");
+        assert_eq!(temp_files(directory.path()).len(), 1);
+
+        file.commit().unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new\n");
+        assert!(temp_files(directory.path()).is_empty());
+    }
+
+    #[test]
+    fn test_uncommitted_file_is_removed() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let target = directory.path().join("out.json");
+
+        let mut file = AtomicFile::create(&target).unwrap();
+        file.write_all(b"{\"data\":[").unwrap();
+        drop(file);
+
+        assert!(!target.exists());
+        assert!(temp_files(directory.path()).is_empty());
+    }
+
+    #[test]
+    fn test_remove_partial_files() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let target = directory.path().join("out.tsv");
+
+        let file = AtomicFile::create(&target).unwrap();
+        remove_partial_files();
+        assert!(temp_files(directory.path()).is_empty());
+
+        // Committing after cleanup fails instead of producing a partial file
+        assert!(file.commit().is_err());
+        assert!(!target.exists());
+    }
+}
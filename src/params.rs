@@ -0,0 +1,481 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+use mysql::Value;
+
+use crate::script::{Placeholder, find_placeholders};
+
+/// Type of a query parameter value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    String,
+    Int,
+    Float,
+    Date,
+    Datetime,
+    Null,
+}
+
+impl ParamKind {
+    /// Every kind, in the order they are listed in error messages
+    const ALL: [Self; 6] = [
+        Self::String,
+        Self::Int,
+        Self::Float,
+        Self::Date,
+        Self::Datetime,
+        Self::Null,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Date => "date",
+            Self::Datetime => "datetime",
+            Self::Null => "null",
+        }
+    }
+
+    /// Looks up a kind by name (case-insensitive)
+    fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(ParamKind::as_str).collect();
+                format!("unknown type '{}' (expected one of: {})", name, names.join(", "))
+            })
+    }
+
+    /// Converts the text of a value to a MySQL value of this kind
+    fn parse(self, text: &str) -> Result<Value, String> {
+        let invalid = || format!("invalid {} value '{}'", self.as_str(), text);
+        match self {
+            Self::String => Ok(Value::Bytes(text.as_bytes().to_vec())),
+            Self::Int => text.trim().parse::<i64>().map(Value::Int).map_err(|_| invalid()),
+            Self::Float => match text.trim().parse::<f64>() {
+                Ok(number) if number.is_finite() => Ok(Value::Double(number)),
+                _ => Err(invalid()),
+            },
+            Self::Date => match parse_datetime(text) {
+                Some(value @ Value::Date(_, _, _, 0, 0, 0, 0)) => Ok(value),
+                Some(_) => Err(format!("invalid date value '{}': use the datetime type for a time of day", text)),
+                None => Err(invalid()),
+            },
+            Self::Datetime => parse_datetime(text).ok_or_else(invalid),
+            Self::Null if text.is_empty() => Ok(Value::NULL),
+            Self::Null => Err(format!("a null parameter takes no value, got '{}'", text)),
+        }
+    }
+}
+
+/// Parses `YYYY-MM-DD[ HH:MM:SS[.ffffff]]`, with a space or `T` before the time
+fn parse_datetime(text: &str) -> Option<Value> {
+    let (date, time) = text.trim().split_once([' ', 'T']).unwrap_or((text.trim(), ""));
+    let mut date_parts = date.splitn(3, '-');
+    let year = date_parts.next()?.parse().ok()?;
+    let month = date_parts.next()?.parse().ok()?;
+    let day = date_parts.next()?.parse().ok()?;
+
+    let (mut hour, mut minute, mut second, mut micros) = (0, 0, 0, 0);
+    if !time.is_empty() {
+        let (clock, fraction) = time.split_once('.').unwrap_or((time, ""));
+        let mut clock_parts = clock.splitn(3, ':');
+        hour = clock_parts.next()?.parse().ok()?;
+        minute = clock_parts.next()?.parse().ok()?;
+        second = clock_parts.next()?.parse().ok()?;
+        if !fraction.is_empty() {
+            if fraction.len() > 6 {
+                return None;
+            }
+            micros = fraction.parse::<u32>().ok()? * 10u32.pow(6 - fraction.len() as u32);
+        }
+    }
+
+    let value = Value::Date(year, month, day, hour, minute, second, micros);
+    crate::validate_value(&value).ok()?;
+    Some(value)
+}
+
+/// Checks that a parameter name can be referenced by a placeholder
+///
+/// Names are words for `:name` placeholders, or numbers for the position of a `?`.
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("missing parameter name".to_string());
+    }
+    if !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+        return Err(format!("invalid parameter name '{}': use letters, digits and '_'", name));
+    }
+    Ok(())
+}
+
+/// A query parameter given on the command line as `NAME[:TYPE]=VALUE`
+///
+/// Values are strings unless a type is given: `id:int=42`, `since:date=2026-01-01`, or
+/// `deleted:null`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryParam {
+    /// Name of the `:name` placeholder, or position of the `?` placeholder, it binds to
+    pub name: String,
+    pub kind: ParamKind,
+    /// The value as written on the command line
+    pub text: String,
+    pub value: Value,
+}
+
+impl FromStr for QueryParam {
+    type Err = String;
+
+    fn from_str(param: &str) -> Result<Self, Self::Err> {
+        let (spec, text) = match param.split_once('=') {
+            Some((spec, text)) => (spec, Some(text)),
+            None => (param, None),
+        };
+        let (name, kind) = match spec.split_once(':') {
+            Some((name, kind)) => (name, ParamKind::from_name(kind)?),
+            None => (spec, ParamKind::String),
+        };
+        check_name(name)?;
+        let text = match (text, kind) {
+            (Some(text), _) => text,
+            (None, ParamKind::Null) => "",
+            (None, _) => return Err(format!("missing value in '{}': expected NAME[:TYPE]=VALUE", param)),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            kind,
+            text: text.to_string(),
+            value: kind.parse(text)?,
+        })
+    }
+}
+
+impl fmt::Display for QueryParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParamKind::String => write!(f, "{}={}", self.name, self.text),
+            ParamKind::Null => write!(f, "{}:null", self.name),
+            kind => write!(f, "{}:{}={}", self.name, kind.as_str(), self.text),
+        }
+    }
+}
+
+/// A statement ready to run, with the values for its placeholders
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundStatement {
+    /// The statement, with `:name` placeholders replaced by `?`
+    pub sql: String,
+    /// Values in placeholder order, or `None` for a statement without placeholders, which
+    /// runs as a plain query instead of a prepared statement
+    pub params: Option<Vec<Value>>,
+}
+
+/// Query parameter values by name
+///
+/// A `:name` placeholder takes the value of the parameter with that name, and the `n`th `?`
+/// placeholder in a statement takes the value of the parameter named `n`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryParams {
+    values: BTreeMap<String, Value>,
+}
+
+impl QueryParams {
+    /// Loads parameters from `--params-file`, then applies `--param` values over them
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid parameters document.
+    pub fn load(file: Option<&Path>, params: &[QueryParam]) -> Result<Self> {
+        let mut loaded = match file {
+            Some(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read params file {}: {}", path.display(), e))?;
+                Self::from_json(&text)
+                    .map_err(|e| anyhow::anyhow!("Invalid --params-file {}: {}", path.display(), e))?
+            },
+            None => Self::default(),
+        };
+        for param in params {
+            loaded.values.insert(param.name.clone(), param.value.clone());
+        }
+        Ok(loaded)
+    }
+
+    /// Parses a parameters document
+    ///
+    /// The document is an object of named values, or an array of values for `?` placeholders.
+    /// JSON strings, numbers, booleans and `null` map to the matching MySQL types; other types
+    /// are given as `{"type": "date", "value": "2026-01-01"}`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is not valid JSON or holds an unsupported value.
+    pub fn from_json(text: &str) -> Result<Self> {
+        let document: serde_json::Value = serde_json::from_str(text)?;
+        let entries: Vec<(String, serde_json::Value)> = match document {
+            serde_json::Value::Object(object) => object.into_iter().collect(),
+            serde_json::Value::Array(array) => array
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| ((index + 1).to_string(), value))
+                .collect(),
+            _ => bail!("expected an object of parameters or an array of values"),
+        };
+
+        let mut values = BTreeMap::new();
+        for (name, value) in entries {
+            check_name(&name).map_err(anyhow::Error::msg)?;
+            let value = json_value(&value).map_err(|e| anyhow::anyhow!("parameter '{}': {}", name, e))?;
+            values.insert(name, value);
+        }
+        Ok(Self { values })
+    }
+
+    /// Returns the value of a parameter
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    /// Binds the parameters to the placeholders of each statement
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a placeholder has no value, a parameter is not used by any
+    /// statement, or a statement mixes `?` and `:name` placeholders.
+    pub fn bind(&self, statements: &[String]) -> Result<Vec<BoundStatement>> {
+        let mut used = BTreeSet::new();
+        let mut bound = Vec::with_capacity(statements.len());
+
+        for (index, statement) in statements.iter().enumerate() {
+            let (sql, placeholders) = find_placeholders(statement);
+            if placeholders.is_empty() {
+                bound.push(BoundStatement { sql, params: None });
+                continue;
+            }
+            let named = placeholders
+                .iter()
+                .filter(|placeholder| matches!(placeholder, Placeholder::Named(_)))
+                .count();
+            if named != 0 && named != placeholders.len() {
+                bail!("Invalid query: statement {} mixes ? and :name placeholders", index + 1);
+            }
+
+            let mut values = Vec::with_capacity(placeholders.len());
+            for placeholder in placeholders {
+                let (name, shown) = match placeholder {
+                    Placeholder::Positional(position) => (position.to_string(), format!("? number {}", position)),
+                    Placeholder::Named(name) => (name.clone(), format!(":{}", name)),
+                };
+                let value = self.values.get(&name).with_context(|| {
+                    format!(
+                        "Missing value for query parameter {} in statement {}: pass --param {}=VALUE or add it to --params-file",
+                        shown,
+                        index + 1,
+                        name
+                    )
+                })?;
+                values.push(value.clone());
+                used.insert(name);
+            }
+            bound.push(BoundStatement {
+                sql,
+                params: Some(values),
+            });
+        }
+
+        let unused: Vec<String> = self
+            .values
+            .keys()
+            .filter(|name| !used.contains(*name))
+            .map(|name| format!("'{}'", name))
+            .collect();
+        match unused.as_slice() {
+            [] => Ok(bound),
+            [name] => bail!("Invalid query parameter {}: the query has no placeholder for it", name),
+            names => bail!("Invalid query parameters {}: the query has no placeholders for them", names.join(", ")),
+        }
+    }
+}
+
+/// Converts a JSON parameter value to a MySQL value
+fn json_value(value: &serde_json::Value) -> Result<Value, String> {
+    match value {
+        serde_json::Value::Null => Ok(Value::NULL),
+        serde_json::Value::Bool(flag) => Ok(Value::Int(i64::from(*flag))),
+        serde_json::Value::Number(number) => match (number.as_i64(), number.as_u64(), number.as_f64()) {
+            (Some(int), _, _) => Ok(Value::Int(int)),
+            (None, Some(uint), _) => Ok(Value::UInt(uint)),
+            (None, None, Some(float)) => Ok(Value::Double(float)),
+            _ => Err(format!("unsupported number {}", number)),
+        },
+        serde_json::Value::String(text) => Ok(Value::Bytes(text.as_bytes().to_vec())),
+        serde_json::Value::Object(object) => {
+            let kind = match object.get("type") {
+                Some(serde_json::Value::String(kind)) => ParamKind::from_name(kind)?,
+                _ => return Err("expected a \"type\" name in a typed value".to_string()),
+            };
+            if let Some(key) = object.keys().find(|key| *key != "type" && *key != "value") {
+                return Err(format!("unexpected key '{}' in a typed value", key));
+            }
+            match object.get("value") {
+                None | Some(serde_json::Value::Null) => kind.parse(""),
+                Some(serde_json::Value::String(text)) => kind.parse(text),
+                Some(serde_json::Value::Number(number)) => kind.parse(&number.to_string()),
+                Some(other) => Err(format!("unsupported value {} in a typed value", other)),
+            }
+        },
+        serde_json::Value::Array(_) => Err("arrays are not supported as parameter values".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(text: &str) -> QueryParam {
+        text.parse().unwrap()
+    }
+
+    fn params(list: &[&str]) -> QueryParams {
+        QueryParams::load(None, &list.iter().map(|text| param(text)).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_param() {
+        assert_eq!(param("name=O'Brien; DROP TABLE x").value, Value::Bytes(b"O'Brien; DROP TABLE x".to_vec()));
+        assert_eq!(param("id:int=42").value, Value::Int(42));
+        assert_eq!(param("ratio:FLOAT=0.5").value, Value::Double(0.5));
+        assert_eq!(param("since:date=2026-10-01").value, Value::Date(2026, 10, 1, 0, 0, 0, 0));
+        assert_eq!(param("at:datetime=2026-10-01 12:30:15.5").value, Value::Date(2026, 10, 1, 12, 30, 15, 500_000));
+        assert_eq!(param("gone:null").value, Value::NULL);
+        assert_eq!(param("empty=").value, Value::Bytes(Vec::new()));
+        assert_eq!(param("1=first").name, "1");
+
+        assert_eq!(param("id:int=42").to_string(), "id:int=42");
+        assert_eq!(param("name=x=y").to_string(), "name=x=y");
+        assert_eq!(param("gone:null").to_string(), "gone:null");
+    }
+
+    #[test]
+    fn test_parse_param_errors() {
+        for (text, message) in [
+            ("id", "missing value"),
+            ("=1", "missing parameter name"),
+            ("my-id=1", "invalid parameter name"),
+            ("id:integer=1", "unknown type 'integer'"),
+            ("id:int=4x", "invalid int value '4x'"),
+            ("day:date=2026-13-01", "invalid date value"),
+            ("day:date=2026-10-01 10:00:00", "use the datetime type"),
+            ("gone:null=x", "takes no value"),
+        ] {
+            let error = text.parse::<QueryParam>().unwrap_err();
+            assert!(error.contains(message), "{}: {}", text, error);
+        }
+    }
+
+    #[test]
+    fn test_from_json() {
+        let loaded = QueryParams::from_json(
+            r#"{"name": "Alice", "id": 7, "big": 18446744073709551615, "ratio": 1.5, "active": true,
+                "deleted": null, "since": {"type": "date", "value": "2026-01-31"}}"#,
+        )
+        .unwrap();
+        let expected = [
+            ("active", Value::Int(1)),
+            ("big", Value::UInt(u64::MAX)),
+            ("deleted", Value::NULL),
+            ("id", Value::Int(7)),
+            ("name", Value::Bytes(b"Alice".to_vec())),
+            ("ratio", Value::Double(1.5)),
+            ("since", Value::Date(2026, 1, 31, 0, 0, 0, 0)),
+        ];
+        assert_eq!(
+            loaded.values,
+            expected
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect()
+        );
+
+        let positional = QueryParams::from_json(r#"[1, "two"]"#).unwrap();
+        assert_eq!(positional.values["1"], Value::Int(1));
+        assert_eq!(positional.values["2"], Value::Bytes(b"two".to_vec()));
+
+        assert!(QueryParams::from_json("42").is_err());
+        assert!(QueryParams::from_json(r#"{"ids": [1, 2]}"#).is_err());
+        assert!(QueryParams::from_json(r#"{"day": {"type": "date", "value": "soon"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_load_prefers_command_line() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("params.json");
+        std::fs::write(&path, r#"{"id": 1, "name": "file"}"#).unwrap();
+
+        let loaded = QueryParams::load(Some(&path), &[param("id:int=2")]).unwrap();
+        assert_eq!(loaded.values["id"], Value::Int(2));
+        assert_eq!(loaded.values["name"], Value::Bytes(b"file".to_vec()));
+
+        std::fs::write(&path, "{").unwrap();
+        let error = QueryParams::load(Some(&path), &[]).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid --params-file"));
+        assert!(error.to_string().contains("EOF while parsing"));
+    }
+
+    #[test]
+    fn test_bind_named_and_positional() {
+        let statements = vec![
+            "SET @limit = 10".to_string(),
+            "SELECT * FROM t WHERE a = :a AND b = :b OR a = :a".to_string(),
+            "SELECT ? + ?".to_string(),
+        ];
+        let bound = params(&["a:int=1", "b=x", "1:int=2", "2:int=3"])
+            .bind(&statements)
+            .unwrap();
+        assert_eq!(
+            bound,
+            vec![
+                BoundStatement {
+                    sql: "SET @limit = 10".to_string(),
+                    params: None,
+                },
+                BoundStatement {
+                    sql: "SELECT * FROM t WHERE a = ? AND b = ? OR a = ?".to_string(),
+                    params: Some(vec![Value::Int(1), Value::Bytes(b"x".to_vec()), Value::Int(1)]),
+                },
+                BoundStatement {
+                    sql: "SELECT ? + ?".to_string(),
+                    params: Some(vec![Value::Int(2), Value::Int(3)]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bind_errors() {
+        let statements = vec!["SELECT :a, :b".to_string()];
+        let error = params(&["a=1"]).bind(&statements).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Missing value for query parameter :b in statement 1: pass --param b=VALUE or add it to --params-file"
+        );
+
+        let error = params(&["a=1", "b=2", "c=3", "d=4"]).bind(&statements).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid query parameters 'c', 'd': the query has no placeholders for them");
+
+        let error = params(&["a=1"]).bind(&["SELECT 1".to_string()]).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid query parameter 'a': the query has no placeholder for it");
+
+        let error = params(&["a=1", "1=2"]).bind(&["SELECT :a, ?".to_string()]).unwrap_err();
+        assert!(error.to_string().contains("mixes ? and :name placeholders"));
+
+        let error = QueryParams::default().bind(&["SELECT ?".to_string()]).unwrap_err();
+        assert!(error.to_string().contains("query parameter ? number 1"));
+    }
+}
@@ -0,0 +1,197 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use mysql::{Column, Value};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use crate::FormatWriter;
+use crate::columnar::{BATCH_ROWS, BatchBuilder};
+
+/// Default number of rows per Parquet row group
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 128 * 1024;
+
+/// Apache Parquet writer that implements the FormatWriter trait
+///
+/// Columns are typed from MySQL column metadata (see [`crate::columnar`]) and written with
+/// Snappy compression. Rows are encoded in small batches and a row group is flushed to the
+/// output every `row_group_size` rows, so memory use is bounded by the row group size rather
+/// than the size of the result set. The file footer is written by [`FormatWriter::finish`].
+pub struct ParquetWriter<W: Write + Send> {
+    output: Option<W>,
+    writer: Option<ArrowWriter<W>>,
+    batch: Option<BatchBuilder>,
+    row_group_size: usize,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    /// Creates a new ParquetWriter over the specified writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            output: Some(writer),
+            writer: None,
+            batch: None,
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+        }
+    }
+
+    /// Sets the maximum number of rows per row group
+    pub fn with_row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = row_group_size.max(1);
+        self
+    }
+
+    /// Starts the Parquet file once the schema is known
+    fn start(&mut self, batch: BatchBuilder) -> Result<()> {
+        let output = self.output.take().context("Parquet header has already been written")?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_row_count(Some(self.row_group_size))
+            .set_created_by(format!("gold_digger {}", env!("CARGO_PKG_VERSION")))
+            .build();
+
+        self.writer = Some(ArrowWriter::try_new(output, batch.schema(), Some(properties))?);
+        self.batch = Some(batch);
+        Ok(())
+    }
+
+    /// Encodes the buffered rows once enough have accumulated
+    fn write_batch_if_full(&mut self) -> Result<()> {
+        if self
+            .batch
+            .as_ref()
+            .is_some_and(|batch| batch.len() >= BATCH_ROWS.min(self.row_group_size))
+        {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Encodes any buffered rows
+    fn write_batch(&mut self) -> Result<()> {
+        if let (Some(writer), Some(batch)) = (self.writer.as_mut(), self.batch.as_mut())
+            && batch.len() > 0
+        {
+            writer.write(&batch.take_batch()?)?;
+        }
+        Ok(())
+    }
+
+    fn batch_mut(&mut self) -> Result<&mut BatchBuilder> {
+        self.batch
+            .as_mut()
+            .context("Parquet header must be written before rows")
+    }
+}
+
+impl<W: Write + Send> FormatWriter for ParquetWriter<W> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        self.start(BatchBuilder::from_names(columns))
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        self.batch_mut()?.append_strings(row)?;
+        self.write_batch_if_full()
+    }
+
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        self.start(BatchBuilder::from_columns(columns)?)
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        self.batch_mut()?.append_values(values)?;
+        self.write_batch_if_full()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if self.writer.is_none() && self.output.is_some() {
+            self.start(BatchBuilder::from_names(&[]))?;
+        }
+        self.write_batch()?;
+        if let Some(writer) = self.writer.as_mut() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Decimal128Type, Int64Type, TimestampMicrosecondType};
+    use arrow_schema::{DataType, TimeUnit};
+    use bytes::Bytes;
+    use mysql::consts::{ColumnFlags, ColumnType};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn text(value: &str) -> Value {
+        Value::Bytes(value.as_bytes().to_vec())
+    }
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_NEWDECIMAL)
+                .with_name(b"price")
+                .with_column_length(12)
+                .with_decimals(2),
+            Column::new(ColumnType::MYSQL_TYPE_DATETIME).with_name(b"created"),
+            Column::new(ColumnType::MYSQL_TYPE_BLOB)
+                .with_name(b"payload")
+                .with_character_set(63)
+                .with_flags(ColumnFlags::BINARY_FLAG),
+        ]
+    }
+
+    #[test]
+    fn test_parquet_typed_round_trip() {
+        let mut output = Vec::new();
+        let mut writer = ParquetWriter::new(&mut output).with_row_group_size(2);
+        writer.write_columns(&columns()).unwrap();
+        for id in 0..5 {
+            writer
+                .write_values(&[
+                    Value::Int(id),
+                    text("19.99"),
+                    text("2024-01-15 10:30:00"),
+                    Value::Bytes(vec![0, 159, 146, 150]),
+                ])
+                .unwrap();
+        }
+        writer
+            .write_values(&[Value::NULL, Value::NULL, Value::NULL, Value::NULL])
+            .unwrap();
+        writer.finalize().unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(output)).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 3);
+        let schema = reader.schema().clone();
+        assert_eq!(schema.field(1).data_type(), &DataType::Decimal128(10, 2));
+        assert_eq!(schema.field(2).data_type(), &DataType::Timestamp(TimeUnit::Microsecond, None));
+        assert_eq!(schema.field(3).data_type(), &DataType::Binary);
+
+        let mut batches = reader.build().unwrap();
+        let batch = batches.next().unwrap().unwrap();
+        assert_eq!(batch.num_rows(), 6);
+        assert_eq!(batch.column(0).as_primitive::<Int64Type>().value(4), 4);
+        assert!(batch.column(0).is_null(5));
+        assert_eq!(batch.column(1).as_primitive::<Decimal128Type>().value(0), 1999);
+        assert_eq!(batch.column(2).as_primitive::<TimestampMicrosecondType>().value(0), 1_705_314_600_000_000);
+        assert_eq!(batch.column(3).as_binary::<i32>().value(0), &[0, 159, 146, 150]);
+    }
+
+    #[test]
+    fn test_parquet_empty_result_is_valid_file() {
+        let mut output = Vec::new();
+        let mut writer = ParquetWriter::new(&mut output);
+        writer.write_columns(&columns()).unwrap();
+        writer.finalize().unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(output)).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 0);
+        assert_eq!(reader.schema().fields().len(), 4);
+    }
+}
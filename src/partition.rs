@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use mysql::{Column, Value};
+
+use crate::FormatWriter;
+use crate::cli::{Compression, OutputFormat};
+use crate::compression::CompressedWriter;
+use crate::formats::{WriterOptions, create_writer};
+use crate::output::{Destination, SharedSink, register_partial, unregister_partial};
+
+/// Default limit on partition files held open at once
+pub const DEFAULT_MAX_OPEN_PARTITIONS: usize = 64;
+
+/// Directory name Hive uses for NULL and empty partition values
+pub const DEFAULT_PARTITION_NAME: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Escapes a column name or value for use in a `column=value` path segment
+///
+/// Path separators, `=`, `%`, control characters and the other characters Hive escapes are
+/// percent-encoded, so every value maps to exactly one directory inside the output directory.
+pub fn escape_path_segment(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '"' | '#' | '%' | '\'' | '*' | '/' | ':' | '=' | '?' | '\\' | '{' | '[' | ']' | '^' | '<' | '>' | '|' => {
+                out.push_str(&format!("%{:02X}", ch as u32));
+            },
+            _ if ch.is_control() => {
+                let mut buf = [0; 4];
+                for byte in ch.encode_utf8(&mut buf).bytes() {
+                    out.push_str(&format!("%{:02X}", byte));
+                }
+            },
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Returns the `column=value` path segment for one partition column
+fn partition_segment(column: &str, value: &Value) -> Result<String> {
+    let value = match value {
+        Value::NULL => DEFAULT_PARTITION_NAME.to_string(),
+        _ => match crate::mysql_value_to_string(value)? {
+            text if text.is_empty() => DEFAULT_PARTITION_NAME.to_string(),
+            text => escape_path_segment(&text),
+        },
+    };
+    Ok(format!("{}={}", escape_path_segment(column), value))
+}
+
+/// A partition file that is currently open
+struct OpenPartition {
+    writer: Box<dyn FormatWriter + 'static>,
+    sink: SharedSink,
+    path: PathBuf,
+    last_used: u64,
+}
+
+impl OpenPartition {
+    /// Finishes the writer and compression, returning the uncommitted file
+    fn close(self) -> Result<(PathBuf, Destination)> {
+        let Self {
+            mut writer, sink, path, ..
+        } = self;
+        writer.finish()?;
+        drop(writer);
+        Ok((path, sink.into_inner()?.finish()?))
+    }
+}
+
+/// Writes each row to a file chosen by the values of the partition columns
+///
+/// Files are laid out Hive-style: for output path `out/part.csv` partitioned by `date`, rows
+/// with `date = 2026-10-01` go to `out/date=2026-10-01/part.csv`. As in Hive, the partition
+/// columns are taken out of the files, since their values are in the path. NULL and empty
+/// values go to the `__HIVE_DEFAULT_PARTITION__` directory.
+///
+/// Every format is supported. At most `max_open` files are open at once; when a row needs
+/// another, the least recently used file is finished and renamed into place, and if that
+/// partition sees more rows later they go to a new numbered file in the same directory
+/// (`part.part-0002.csv`). Sorting the query by the partition columns keeps every partition
+/// in a single file.
+///
+/// Files renamed into place early stay registered for removal on an error exit until
+/// [`PartitionedOutput::finish`], so a failed run leaves no partial data behind.
+pub struct PartitionedOutput {
+    path: PathBuf,
+    format: OutputFormat,
+    compression: Compression,
+    level: Option<i32>,
+    partition_by: Vec<String>,
+    max_open: usize,
+    open: HashMap<PathBuf, OpenPartition>,
+    /// Files started so far in each partition directory
+    file_counts: HashMap<PathBuf, usize>,
+    /// Finished files waiting for [`PartitionedOutput::finish`]
+    pending: Vec<Destination>,
+    /// Files renamed into place before the end of the run
+    committed: Vec<PathBuf>,
+    clock: u64,
+}
+
+impl PartitionedOutput {
+    /// Creates a partitioned output under the directory of `path`, named after its file name
+    pub fn new(
+        path: &Path,
+        format: OutputFormat,
+        compression: Compression,
+        level: Option<i32>,
+        partition_by: Vec<String>,
+        max_open: usize,
+    ) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            format,
+            compression,
+            level,
+            partition_by,
+            max_open: max_open.max(1),
+            open: HashMap::new(),
+            file_counts: HashMap::new(),
+            pending: Vec::new(),
+            committed: Vec::new(),
+            clock: 0,
+        }
+    }
+
+    /// Returns the number of files written
+    pub fn file_count(&self) -> usize {
+        self.file_counts.values().sum()
+    }
+
+    /// Routes each row to the file for its partition
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a partition column is not in the result, or if a file cannot be
+    /// created, written, or committed.
+    pub fn write<R>(&mut self, columns: &[Column], rows: R, options: &WriterOptions) -> Result<()>
+    where
+        R: Iterator<Item = Vec<Value>>,
+    {
+        // An empty result has no partitions, so no files are written
+        if columns.is_empty() {
+            return Ok(());
+        }
+        let names = crate::column_names(columns);
+        let partition_indices =
+            self.partition_by
+                .iter()
+                .map(|column| {
+                    names.iter().position(|name| name == column).with_context(|| {
+                        format!("Invalid --partition-by: column '{}' is not in the query result", column)
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+        let data_columns: Vec<Column> = columns
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !partition_indices.contains(index))
+            .map(|(_, column)| column.clone())
+            .collect();
+
+        for row in rows {
+            let mut directory = self.path.parent().unwrap_or(Path::new("")).to_path_buf();
+            for (&index, column) in partition_indices.iter().zip(&self.partition_by) {
+                directory.push(partition_segment(column, &row[index])?);
+            }
+            let values: Vec<Value> = row
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| !partition_indices.contains(index))
+                .map(|(_, value)| value)
+                .collect();
+
+            self.clock += 1;
+            if !self.open.contains_key(&directory) {
+                let partition = self.open_partition(&directory, &data_columns, options)?;
+                self.open.insert(directory.clone(), partition);
+            }
+            let partition = self.open.get_mut(&directory).expect("partition was just opened");
+            partition.last_used = self.clock;
+            partition.writer.write_values(&values)?;
+        }
+
+        for (_, partition) in self.open.drain() {
+            let (_, destination) = partition.close()?;
+            self.pending.push(destination);
+        }
+        Ok(())
+    }
+
+    /// Commits every remaining file and keeps the files committed earlier
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be flushed, synced, or renamed.
+    pub fn finish(self) -> Result<()> {
+        for destination in self.pending {
+            destination.commit()?;
+        }
+        for path in &self.committed {
+            unregister_partial(path);
+        }
+        Ok(())
+    }
+
+    /// Opens the next file for a partition directory, closing the least recently used file
+    /// first if the limit is reached
+    fn open_partition(
+        &mut self,
+        directory: &Path,
+        columns: &[Column],
+        options: &WriterOptions,
+    ) -> Result<OpenPartition> {
+        if self.open.len() >= self.max_open {
+            self.evict_least_recently_used()?;
+        }
+
+        std::fs::create_dir_all(directory).map_err(|e| {
+            anyhow::anyhow!("Failed to create output file: cannot create {}: {}", directory.display(), e)
+        })?;
+        let count = self.file_counts.entry(directory.to_path_buf()).or_insert(0);
+        *count += 1;
+        let file = directory.join(self.path.file_name().unwrap_or_default());
+        let path = match *count {
+            1 => file,
+            number => crate::split::part_path(&file, number),
+        };
+
+        let destination = Destination::open(&path)?;
+        let sink = SharedSink::new(CompressedWriter::new(destination, self.compression, self.level)?);
+        let mut writer = create_writer(self.format, Box::new(sink.clone()), options)?;
+        writer.write_columns(columns)?;
+        Ok(OpenPartition {
+            writer,
+            sink,
+            path,
+            last_used: self.clock,
+        })
+    }
+
+    /// Finishes the open file that has gone longest without a row and moves it into place
+    fn evict_least_recently_used(&mut self) -> Result<()> {
+        let directory = self
+            .open
+            .iter()
+            .min_by_key(|(_, partition)| partition.last_used)
+            .map(|(directory, _)| directory.clone());
+        if let Some(partition) = directory.and_then(|directory| self.open.remove(&directory)) {
+            let (path, destination) = partition.close()?;
+            destination.commit()?;
+            register_partial(&path);
+            self.committed.push(path);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::PARTIAL_FILES_TEST_LOCK;
+    use mysql::consts::ColumnType;
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new(ColumnType::MYSQL_TYPE_DATE).with_name(b"day"),
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+        ]
+    }
+
+    fn row(day: &str, id: i64) -> Vec<Value> {
+        vec![Value::Bytes(day.as_bytes().to_vec()), Value::Int(id)]
+    }
+
+    #[cfg(any(feature = "csv", feature = "json"))]
+    fn read(path: PathBuf) -> String {
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+    }
+
+    #[test]
+    fn test_escape_path_segment() {
+        assert_eq!(escape_path_segment("2026-10-01"), "2026-10-01");
+        assert_eq!(escape_path_segment("a/b=c"), "a%2Fb%3Dc");
+        assert_eq!(escape_path_segment("../x"), "..%2Fx");
+        assert_eq!(escape_path_segment("100%\n"), "100%25%0A");
+        assert_eq!(partition_segment("day", &Value::NULL).unwrap(), "day=__HIVE_DEFAULT_PARTITION__");
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_partitioned_csv_layout() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("part.csv");
+        let rows = vec![
+            row("2026-10-01", 1),
+            row("2026-10-02", 2),
+            row("2026-10-01", 3),
+            row("", 4),
+        ];
+
+        let mut output =
+            PartitionedOutput::new(&path, OutputFormat::Csv, Compression::None, None, vec!["day".into()], 8);
+        output
+            .write(&columns(), rows.into_iter(), &WriterOptions::default())
+            .unwrap();
+        assert_eq!(output.file_count(), 3);
+        output.finish().unwrap();
+
+        let root = directory.path();
+        assert_eq!(read(root.join("day=2026-10-01/part.csv")), "id\n1\n3\n");
+        assert_eq!(read(root.join("day=2026-10-02/part.csv")), "id\n2\n");
+        assert_eq!(read(root.join("day=__HIVE_DEFAULT_PARTITION__/part.csv")), "id\n4\n");
+        assert!(!path.exists());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_open_file_limit_starts_new_files() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("part.json");
+        let rows = vec![row("a", 1), row("b", 2), row("a", 3), row("c", 4), row("b", 5)];
+
+        let mut output =
+            PartitionedOutput::new(&path, OutputFormat::Json, Compression::None, None, vec!["day".into()], 2);
+        output
+            .write(&columns(), rows.into_iter(), &WriterOptions::default())
+            .unwrap();
+        output.finish().unwrap();
+
+        // Opening "c" closes "b" (least recently used), so b's last row starts a second file
+        let root = directory.path();
+        assert_eq!(read(root.join("day=a/part.json")), r#"{"data":[{"id":1},{"id":3}]}"#);
+        assert_eq!(read(root.join("day=b/part.json")), r#"{"data":[{"id":2}]}"#);
+        assert_eq!(read(root.join("day=b/part.part-0002.json")), r#"{"data":[{"id":5}]}"#);
+        assert_eq!(read(root.join("day=c/part.json")), r#"{"data":[{"id":4}]}"#);
+    }
+
+    #[test]
+    fn test_partition_by_two_columns_with_sql_output() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("orders.sql");
+        let options = WriterOptions {
+            sql_table: Some("orders".to_string()),
+            ..WriterOptions::default()
+        };
+        let partition_by = vec!["id".to_string(), "day".to_string()];
+
+        let mut output = PartitionedOutput::new(&path, OutputFormat::Sql, Compression::None, None, partition_by, 8);
+        output
+            .write(&columns(), vec![row("a/b", 7)].into_iter(), &options)
+            .unwrap();
+        output.finish().unwrap();
+
+        // Every column is a partition column, so the file holds no INSERT statements
+        let file = directory.path().join("id=7").join("day=a%2Fb").join("orders.sql");
+        assert!(file.exists(), "{}", file.display());
+    }
+
+    #[test]
+    fn test_unknown_partition_column() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("part.csv");
+
+        let mut output =
+            PartitionedOutput::new(&path, OutputFormat::Csv, Compression::None, None, vec!["month".into()], 8);
+        let err = output
+            .write(&columns(), vec![row("a", 1)].into_iter(), &WriterOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("column 'month' is not in the query result"), "{}", err);
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use mysql::{Column, Value};
+
+use crate::FormatWriter;
+use crate::cli::{Compression, OutputFormat};
+use crate::compression::CompressedWriter;
+use crate::formats::{WriterOptions, create_result_sets_writer};
+use crate::output::{Destination, SharedSink, with_name_suffix};
+
+/// Returns the path of a numbered result set, keeping any compression extension last
+///
+/// `out/data.csv.gz` becomes `out/data.2.csv.gz` for the second result set.
+pub fn result_set_path(path: &Path, number: usize) -> PathBuf {
+    with_name_suffix(path, &format!(".{}", number))
+}
+
+/// Writes every result set of a query into a single document
+///
+/// Each result set becomes one named array (`result_1`, `result_2`, ...) of a JSON document,
+/// in the order the server returned them. Result sets without rows are kept as empty arrays
+/// so the names always match the position of the statement or `SELECT` that produced them.
+pub struct CombinedOutput {
+    writer: Box<dyn FormatWriter + 'static>,
+    sink: SharedSink,
+}
+
+impl CombinedOutput {
+    /// Opens the output, which is renamed into place by committing the result of
+    /// [`CombinedOutput::finish`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the format cannot hold several result sets or the output cannot
+    /// be created.
+    pub fn open(
+        path: &Path,
+        format: OutputFormat,
+        compression: Compression,
+        level: Option<i32>,
+        options: &WriterOptions,
+    ) -> Result<Self> {
+        let sink = SharedSink::new(CompressedWriter::new(Destination::open(path)?, compression, level)?);
+        let writer = create_result_sets_writer(format, Box::new(sink.clone()), options)?;
+        Ok(Self { writer, sink })
+    }
+
+    /// Writes one result set
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding or writing a row fails.
+    pub fn write<R>(&mut self, columns: &[Column], rows: R) -> Result<()>
+    where
+        R: Iterator<Item = Vec<Value>>,
+    {
+        self.writer.write_columns(columns)?;
+        for row in rows {
+            self.writer.write_values(&row)?;
+        }
+        Ok(())
+    }
+
+    /// Closes the document and finishes compression, returning the uncommitted output
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the remaining output cannot be written.
+    pub fn finish(self) -> Result<Destination> {
+        let Self { mut writer, sink } = self;
+        writer.finish()?;
+        drop(writer);
+        sink.into_inner()?.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::PARTIAL_FILES_TEST_LOCK;
+    #[cfg(feature = "json")]
+    use mysql::consts::ColumnType;
+
+    #[cfg(feature = "json")]
+    fn column(name: &str) -> Column {
+        Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+            .with_name(name.as_bytes())
+            .with_character_set(33)
+    }
+
+    #[test]
+    fn test_result_set_path() {
+        assert_eq!(result_set_path(Path::new("out/data.csv"), 1), Path::new("out/data.1.csv"));
+        assert_eq!(result_set_path(Path::new("out/data.json.gz"), 12), Path::new("out/data.12.json.gz"));
+        assert_eq!(result_set_path(Path::new("data"), 2), Path::new("data.2"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_combined_output_writes_one_document() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("out.json");
+
+        let mut output =
+            CombinedOutput::open(&path, OutputFormat::Json, Compression::None, None, &WriterOptions::default())
+                .unwrap();
+        output
+            .write(&[column("a")], vec![vec![Value::Bytes(b"1".to_vec())]].into_iter())
+            .unwrap();
+        output
+            .write(&[column("b")], vec![vec![Value::Bytes(b"2".to_vec())]].into_iter())
+            .unwrap();
+        let destination = output.finish().unwrap();
+        assert!(!path.exists());
+        destination.commit().unwrap();
+
+        let document: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(document, serde_json::json!({"result_1": [{"a": "1"}], "result_2": [{"b": "2"}]}));
+    }
+
+    #[test]
+    fn test_combined_output_rejects_other_formats() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("out.csv");
+
+        let error = CombinedOutput::open(&path, OutputFormat::Csv, Compression::None, None, &WriterOptions::default())
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("csv output cannot hold several result sets"));
+        assert!(!path.exists());
+    }
+}
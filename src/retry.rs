@@ -0,0 +1,206 @@
+use std::time::Duration;
+
+use mysql::DriverError;
+
+use crate::timeout::format_duration;
+
+/// Wait before the first retry when `--retry-backoff` is not given
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Server errors that go away when the statement is run again: lock wait timeout
+/// (ER_LOCK_WAIT_TIMEOUT) and deadlock (ER_LOCK_DEADLOCK)
+const LOCK_ERROR_CODES: &[u16] = &[1205, 1213];
+
+/// Client errors for a server that cannot be reached or went away: CR_CONNECTION_ERROR,
+/// CR_CONN_HOST_ERROR, CR_SERVER_GONE_ERROR and CR_SERVER_LOST
+const CONNECTION_ERROR_CODES: &[u16] = &[2002, 2003, 2006, 2013];
+
+/// Why a failed operation may succeed when tried again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transient {
+    /// The server could not be reached or the connection was lost, so a new one is needed
+    Connection,
+    /// The statement lost a lock wait or a deadlock, and can run again on the same connection
+    Lock,
+}
+
+/// Returns why an error is transient, or `None` if trying again would fail the same way
+///
+/// The driver reports unreachable servers and lost connections as I/O, codec and connect errors
+/// rather than the client error codes 2002 to 2013, so those count as connection errors too.
+pub fn transient_error(error: &mysql::Error) -> Option<Transient> {
+    match error {
+        mysql::Error::MySqlError(e) if LOCK_ERROR_CODES.contains(&e.code) => Some(Transient::Lock),
+        mysql::Error::MySqlError(e) if CONNECTION_ERROR_CODES.contains(&e.code) => Some(Transient::Connection),
+        mysql::Error::IoError(_) => Some(Transient::Connection),
+        // The packet codec wraps I/O errors on an established connection, such as a reset
+        mysql::Error::CodecError(e) if std::error::Error::source(e).is_some_and(|e| e.is::<std::io::Error>()) => {
+            Some(Transient::Connection)
+        },
+        mysql::Error::DriverError(DriverError::ConnectTimeout | DriverError::CouldNotConnect(Some(_))) => {
+            Some(Transient::Connection)
+        },
+        _ => None,
+    }
+}
+
+/// What is being retried, for the attempt log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Opening the database connection
+    Connect,
+    /// Running a read statement
+    Query,
+}
+
+impl Operation {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Connect => "connect to",
+            Self::Query => "run query on",
+        }
+    }
+}
+
+/// How often and how patiently transient failures are retried
+#[derive(Debug, Clone, Default)]
+pub struct RetryPolicy {
+    /// Attempts after the first one; 0 disables retrying
+    pub retries: u32,
+    /// Wait before the first retry, doubled before each later one
+    pub backoff: Duration,
+    /// Database URL shown in the attempt log, already redacted
+    pub target: String,
+    /// Whether to leave attempts out of stderr
+    pub quiet: bool,
+}
+
+impl RetryPolicy {
+    /// Builds the policy of `--retries` and `--retry-backoff` for a database URL
+    pub fn new(retries: Option<u32>, backoff: Option<Duration>, database_url: &str, quiet: bool) -> Self {
+        Self {
+            retries: retries.unwrap_or(0),
+            backoff: backoff.unwrap_or(DEFAULT_RETRY_BACKOFF),
+            target: crate::tls::redact_url(database_url),
+            quiet,
+        }
+    }
+
+    /// Returns the wait before retry number `retry`, counting from 1
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.backoff.saturating_mul(factor)
+    }
+
+    /// Logs a failed attempt and waits before retry number `retry`, counting from 1
+    pub fn wait(&self, operation: Operation, retry: u32, error: &mysql::Error) {
+        let delay = self.delay(retry);
+        if !self.quiet {
+            eprintln!(
+                "Attempt {} of {} to {} {} failed: {}; retrying in {}",
+                retry,
+                self.retries + 1,
+                operation.as_str(),
+                self.target,
+                error,
+                format_duration(delay)
+            );
+        }
+        std::thread::sleep(delay);
+        if !self.quiet {
+            eprintln!("Attempt {} of {} to {} {}...", retry + 1, self.retries + 1, operation.as_str(), self.target);
+        }
+    }
+
+    /// Runs an operation, retrying it while it fails with a transient error
+    ///
+    /// # Errors
+    ///
+    /// Returns the last error once it is not transient or the retries are used up.
+    pub fn run<T>(&self, operation: Operation, mut attempt: impl FnMut() -> mysql::Result<T>) -> mysql::Result<T> {
+        let mut retry = 0;
+        loop {
+            match attempt() {
+                Err(e) if retry < self.retries && transient_error(&e).is_some() => {
+                    retry += 1;
+                    self.wait(operation, retry, &e);
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::io::ErrorKind;
+
+    fn server_error(code: u16) -> mysql::Error {
+        mysql::Error::MySqlError(mysql::MySqlError {
+            state: "HY000".to_string(),
+            message: "test".to_string(),
+            code,
+        })
+    }
+
+    #[test]
+    fn test_transient_error() {
+        assert_eq!(transient_error(&server_error(1213)), Some(Transient::Lock));
+        assert_eq!(transient_error(&server_error(1205)), Some(Transient::Lock));
+        assert_eq!(transient_error(&server_error(2013)), Some(Transient::Connection));
+        assert_eq!(
+            transient_error(&mysql::Error::IoError(ErrorKind::ConnectionRefused.into())),
+            Some(Transient::Connection)
+        );
+        assert_eq!(
+            transient_error(&mysql::Error::DriverError(DriverError::ConnectTimeout)),
+            Some(Transient::Connection)
+        );
+
+        assert_eq!(transient_error(&server_error(1045)), None);
+        assert_eq!(transient_error(&server_error(1064)), None);
+        assert_eq!(transient_error(&mysql::Error::DriverError(DriverError::CouldNotConnect(None))), None);
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        let policy = RetryPolicy::new(Some(4), Some(Duration::from_millis(500)), "mysql://u:p@db/app", true);
+        let delays: Vec<_> = (1..=4).map(|retry| policy.delay(retry)).collect();
+        assert_eq!(delays, [500, 1000, 2000, 4000].map(Duration::from_millis));
+        assert_eq!(policy.target, "mysql://***REDACTED***:***REDACTED***@db/app");
+    }
+
+    #[test]
+    fn test_run_retries_transient_errors() {
+        let policy = RetryPolicy::new(Some(2), Some(Duration::from_millis(1)), "mysql://db/app", true);
+
+        let attempts = Cell::new(0);
+        let result = policy.run(Operation::Connect, || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                Err(server_error(2003))
+            } else {
+                Ok(attempts.get())
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        attempts.set(0);
+        let result = policy.run(Operation::Connect, || {
+            attempts.set(attempts.get() + 1);
+            Err::<(), _>(server_error(2003))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 3);
+
+        attempts.set(0);
+        let result = policy.run(Operation::Connect, || {
+            attempts.set(attempts.get() + 1);
+            Err::<(), _>(server_error(1045))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+}
@@ -0,0 +1,564 @@
+use anyhow::{Result, bail};
+
+/// Statement delimiter in effect until a `DELIMITER` directive changes it
+pub const DEFAULT_DELIMITER: &str = ";";
+
+/// Splits a query script into statements, the way the mysql command-line client does
+///
+/// Statements end at the current delimiter, which is `;` until a `DELIMITER` directive
+/// changes it. This lets a script define stored programs whose bodies contain `;`:
+///
+/// ```sql
+/// DELIMITER //
+/// CREATE PROCEDURE report() BEGIN SELECT 1; SELECT 2; END //
+/// DELIMITER ;
+/// CALL report();
+/// ```
+///
+/// A directive is recognized at the start of a statement, in any letter case, and its
+/// delimiter is the first word after it; the rest of the line is ignored. Delimiters inside
+/// quoted strings, quoted identifiers and comments do not end a statement.
+///
+/// Statements are returned trimmed and without their delimiter. Comments inside them are
+/// kept, since optimizer hints and versioned comments mean something to the server, but
+/// statements made only of comments and whitespace are dropped.
+///
+/// # Errors
+///
+/// Returns an error if a `DELIMITER` directive has no delimiter or one containing a backslash,
+/// or if the script contains no statements.
+pub fn split_statements(script: &str) -> Result<Vec<String>> {
+    let bytes = script.as_bytes();
+    let mut delimiter = DEFAULT_DELIMITER.to_string();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    // Whether the current statement has anything besides whitespace and comments
+    let mut has_code = false;
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let rest = &bytes[pos..];
+        if !has_code && is_delimiter_directive(rest) {
+            let line_end = line_end(bytes, pos);
+            let argument = &script[pos + "DELIMITER".len()..line_end];
+            let line = bytes[..pos].iter().filter(|&&b| b == b'\n').count() + 1;
+            delimiter = match argument.split_whitespace().next() {
+                Some(word) if word.contains('\\') => {
+                    bail!("Invalid DELIMITER directive on line {}: the delimiter cannot contain '\\'", line)
+                },
+                Some(word) => word.to_string(),
+                None => bail!("Invalid DELIMITER directive on line {}: missing delimiter", line),
+            };
+            pos = line_end;
+            start = pos;
+            continue;
+        }
+
+        if rest.starts_with(delimiter.as_bytes()) {
+            if has_code {
+                statements.push(script[start..pos].trim().to_string());
+            }
+            pos += delimiter.len();
+            start = pos;
+            has_code = false;
+            continue;
+        }
+
+        match bytes[pos] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                pos = quoted_end(bytes, pos, quote, true);
+                has_code = true;
+            },
+            b'#' => pos = line_end(bytes, pos),
+            b'-' if is_dash_comment(rest) => pos = line_end(bytes, pos),
+            b'/' if rest.starts_with(b"/*") => {
+                // Executable comments and optimizer hints are run by the server
+                has_code |= executable_comment_start(rest).is_some() || rest.starts_with(b"/*+");
+                pos = comment_end(bytes, pos);
+            },
+            byte => {
+                has_code |= !byte.is_ascii_whitespace();
+                pos += 1;
+            },
+        }
+    }
+    if has_code {
+        statements.push(script[start..].trim().to_string());
+    }
+
+    if statements.is_empty() {
+        bail!("Invalid query: no SQL statements found");
+    }
+    Ok(statements)
+}
+
+/// A parameter placeholder in a statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Placeholder {
+    /// `?`, bound by its position in the statement, counting from 1
+    Positional(usize),
+    /// `:name`, bound by name
+    Named(String),
+}
+
+/// Finds the `?` and `:name` placeholders in a statement
+///
+/// Returns the statement with every placeholder replaced by `?`, ready to be prepared, and the
+/// placeholders in order. Placeholders inside quoted strings, quoted identifiers and comments
+/// are ignored, as is a colon directly after a word, so stored program labels such as
+/// `retry:LOOP` are not mistaken for parameters.
+pub fn find_placeholders(statement: &str) -> (String, Vec<Placeholder>) {
+    let bytes = statement.as_bytes();
+    let mut sql = String::with_capacity(statement.len());
+    let mut placeholders = Vec::new();
+    let mut copied = 0;
+    let mut positional = 0;
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let rest = &bytes[pos..];
+        match bytes[pos] {
+            quote @ (b'\'' | b'"' | b'`') => pos = quoted_end(bytes, pos, quote, true),
+            b'#' => pos = line_end(bytes, pos),
+            b'-' if is_dash_comment(rest) => pos = line_end(bytes, pos),
+            b'/' if rest.starts_with(b"/*") => pos = comment_end(bytes, pos),
+            b'?' => {
+                positional += 1;
+                placeholders.push(Placeholder::Positional(positional));
+                pos += 1;
+            },
+            b':' if rest.get(1).is_some_and(|&b| b.is_ascii_alphabetic() || b == b'_')
+                && !pos.checked_sub(1).is_some_and(|before| is_word_byte(bytes[before])) =>
+            {
+                let end = pos + 1 + rest[1..].iter().take_while(|&&b| is_word_byte(b)).count();
+                sql.push_str(&statement[copied..pos]);
+                sql.push('?');
+                placeholders.push(Placeholder::Named(statement[pos + 1..end].to_string()));
+                copied = end;
+                pos = end;
+            },
+            _ => pos += 1,
+        }
+    }
+    sql.push_str(&statement[copied..]);
+    (sql, placeholders)
+}
+
+/// Statement keywords that only read data, so running the statement again is harmless
+const READ_KEYWORDS: &[&str] = &[
+    "SELECT", "SHOW", "WITH", "EXPLAIN", "DESCRIBE", "DESC", "TABLE", "VALUES",
+];
+
+/// Statement keywords read-only mode runs
+pub const READ_ONLY_KEYWORDS: &[&str] = &["SELECT", "SHOW", "WITH", "EXPLAIN", "DESCRIBE", "DESC", "CALL"];
+
+/// Statement keywords that can introduce a data-changing statement: common table expressions
+/// precede one, and `EXPLAIN ANALYZE` runs the statement it explains
+const WRAPPING_KEYWORDS: &[&str] = &["WITH", "EXPLAIN", "DESCRIBE", "DESC"];
+
+/// Keywords that make a `WITH` or `EXPLAIN` statement change data
+const WRITE_KEYWORDS: &[&str] = &["INSERT", "UPDATE", "DELETE", "REPLACE"];
+
+/// Returns true if a statement only reads data, judging by its first keyword
+///
+/// A `WITH` or `EXPLAIN` statement counts as a read unless it contains `INSERT`, `UPDATE`,
+/// `DELETE` or `REPLACE`, since it can wrap a data-changing statement.
+pub fn is_read_statement(statement: &str) -> bool {
+    let tokens: Vec<&str> = tokens(statement, true).collect();
+    match tokens.iter().find(|token| is_word(token)) {
+        Some(first) if is_keyword(first, WRAPPING_KEYWORDS) => find_write(&tokens).is_none(),
+        Some(first) => is_keyword(first, READ_KEYWORDS),
+        None => false,
+    }
+}
+
+/// Returns the first keyword among `tokens` that starts a data-changing statement
+///
+/// `INSERT` and `REPLACE` followed by `(` are calls to the string functions of the same name.
+fn find_write<'a>(tokens: &[&'a str]) -> Option<&'a str> {
+    tokens.iter().enumerate().find_map(|(index, &token)| {
+        let call = is_keyword(token, &["INSERT", "REPLACE"]) && tokens.get(index + 1) == Some(&"(");
+        (is_keyword(token, WRITE_KEYWORDS) && !call).then_some(token)
+    })
+}
+
+/// Checks that every statement of a script is one read-only mode runs
+///
+/// Statements must start with `SELECT`, `SHOW`, `WITH`, `EXPLAIN` (or its synonyms `DESCRIBE`
+/// and `DESC`) or `CALL`. A `WITH` or `EXPLAIN` statement must not contain `INSERT`, `UPDATE`,
+/// `DELETE` or `REPLACE`, no statement may write a server file with `INTO OUTFILE` or
+/// `INTO DUMPFILE`, and none may hide a second statement in an executable comment such as
+/// `/*! ; DROP TABLE t */` or MariaDB's `/*M! ; DROP TABLE t */`.
+///
+/// Statements are checked both with backslash escapes in strings and without them, as under
+/// the `NO_BACKSLASH_ESCAPES` SQL mode, so a string cannot end in a different place for the
+/// server than for the check.
+///
+/// This only looks at the text of the statements, so it cannot see what a called procedure
+/// does; the read-only transaction the statements run in stops those writes on the server.
+///
+/// # Errors
+///
+/// Returns an error naming the first statement that read-only mode refuses and why.
+pub fn check_read_only(statements: &[String]) -> Result<()> {
+    for (index, statement) in statements.iter().enumerate() {
+        if let Some(reason) = read_only_violation(statement) {
+            bail!(
+                "Invalid query: read-only mode refuses statement {} because {}; pass --allow-writes to run statements that change data",
+                index + 1,
+                reason
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns why read-only mode refuses a statement, or `None` if it runs it
+fn read_only_violation(statement: &str) -> Option<String> {
+    read_only_violation_as(statement, true).or_else(|| read_only_violation_as(statement, false))
+}
+
+/// Returns why read-only mode refuses a statement, reading strings with or without backslash
+/// escapes
+fn read_only_violation_as(statement: &str, backslash_escapes: bool) -> Option<String> {
+    let tokens: Vec<&str> = tokens(statement, backslash_escapes).collect();
+    let mut words = tokens.iter().copied().filter(|token| is_word(token));
+
+    let first = words.next().unwrap_or_default();
+    if !is_keyword(first, READ_ONLY_KEYWORDS) {
+        let allowed = "SELECT, SHOW, WITH, EXPLAIN or CALL";
+        return Some(match first {
+            "" => format!("it does not start with {}", allowed),
+            _ => format!("{} is not one of {}", first.to_ascii_uppercase(), allowed),
+        });
+    }
+    if is_keyword(first, WRAPPING_KEYWORDS)
+        && let Some(write) = find_write(&tokens)
+    {
+        return Some(format!("{} ... {} changes data", first.to_ascii_uppercase(), write.to_ascii_uppercase()));
+    }
+    let mut previous = first;
+    for word in words {
+        if previous.eq_ignore_ascii_case("INTO") && is_keyword(word, &["OUTFILE", "DUMPFILE"]) {
+            return Some(format!("INTO {} writes a file on the server", word.to_ascii_uppercase()));
+        }
+        previous = word;
+    }
+    if tokens.contains(&";") {
+        return Some("it contains more than one statement".to_string());
+    }
+    None
+}
+
+/// Returns true if a word is one of the given keywords, in any letter case
+fn is_keyword(word: &str, keywords: &[&str]) -> bool {
+    keywords.iter().any(|keyword| word.eq_ignore_ascii_case(keyword))
+}
+
+/// Returns true if a token is an unquoted word rather than punctuation
+fn is_word(token: &str) -> bool {
+    is_word_byte(token.as_bytes()[0])
+}
+
+/// Returns the tokens of a statement that the server runs: unquoted words, and punctuation one
+/// character at a time
+///
+/// Strings, quoted identifiers and comments are skipped, except for the contents of executable
+/// comments such as `/*!50000 ... */` and `/*M! ... */`, which the server runs as part of the
+/// statement. `backslash_escapes` tells whether a backslash escapes the next character in a
+/// string, which the `NO_BACKSLASH_ESCAPES` SQL mode turns off.
+fn tokens(statement: &str, backslash_escapes: bool) -> impl Iterator<Item = &str> {
+    let bytes = statement.as_bytes();
+    let mut pos = 0;
+    let mut in_versioned_comment = false;
+    std::iter::from_fn(move || {
+        while pos < bytes.len() {
+            let rest = &bytes[pos..];
+            match bytes[pos] {
+                quote @ (b'\'' | b'"' | b'`') => pos = quoted_end(bytes, pos, quote, backslash_escapes),
+                b'#' => pos = line_end(bytes, pos),
+                b'-' if is_dash_comment(rest) => pos = line_end(bytes, pos),
+                b'/' if rest.starts_with(b"/*") => match executable_comment_start(rest) {
+                    Some(start) => {
+                        pos += start;
+                        in_versioned_comment = true;
+                    },
+                    None => pos = comment_end(bytes, pos),
+                },
+                b'*' if in_versioned_comment && rest.starts_with(b"*/") => {
+                    pos += 2;
+                    in_versioned_comment = false;
+                },
+                byte if is_word_byte(byte) => {
+                    let start = pos;
+                    pos += rest.iter().take_while(|&&b| is_word_byte(b)).count();
+                    return Some(&statement[start..pos]);
+                },
+                byte if byte.is_ascii_punctuation() => {
+                    pos += 1;
+                    return Some(&statement[pos - 1..pos]);
+                },
+                _ => pos += 1,
+            }
+        }
+        None
+    })
+}
+
+/// Returns true for bytes that can appear in an unquoted identifier
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
+}
+
+/// Returns the length of the opening of an executable comment at the start of `rest`, such as
+/// `/*!`, `/*!50000`, `/*M!` or `/*M!100100`, including the optional server version the
+/// comment applies from
+fn executable_comment_start(rest: &[u8]) -> Option<usize> {
+    let opening = if rest.starts_with(b"/*!") {
+        3
+    } else if rest.starts_with(b"/*M!") {
+        4
+    } else {
+        return None;
+    };
+    Some(opening + rest[opening..].iter().take_while(|b| b.is_ascii_digit()).count())
+}
+
+/// Returns true if `rest` starts with a `DELIMITER` directive
+fn is_delimiter_directive(rest: &[u8]) -> bool {
+    const DIRECTIVE: &[u8] = b"DELIMITER";
+    rest.len() >= DIRECTIVE.len()
+        && rest[..DIRECTIVE.len()].eq_ignore_ascii_case(DIRECTIVE)
+        && rest.get(DIRECTIVE.len()).is_none_or(|b| b.is_ascii_whitespace())
+}
+
+/// Returns true if `rest` starts a `-- ` comment, which MySQL requires to be followed by
+/// whitespace or a control character
+fn is_dash_comment(rest: &[u8]) -> bool {
+    rest.starts_with(b"--")
+        && rest
+            .get(2)
+            .is_none_or(|&b| b.is_ascii_whitespace() || b.is_ascii_control())
+}
+
+/// Returns the position just past the end of the line containing `pos`
+fn line_end(bytes: &[u8], pos: usize) -> usize {
+    bytes[pos..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |offset| pos + offset + 1)
+}
+
+/// Returns the position just past the `*/` closing the comment that starts at `pos`
+fn comment_end(bytes: &[u8], pos: usize) -> usize {
+    bytes[pos + 2..]
+        .windows(2)
+        .position(|window| window == b"*/")
+        .map_or(bytes.len(), |offset| pos + 2 + offset + 2)
+}
+
+/// Returns the position just past the closing quote of the string or identifier at `pos`
+///
+/// A doubled quote stands for the quote itself, and in strings a backslash escapes the
+/// next character unless `backslash_escapes` is false. An unterminated quote runs to the end
+/// of the script.
+fn quoted_end(bytes: &[u8], pos: usize, quote: u8, backslash_escapes: bool) -> usize {
+    let mut i = pos + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if backslash_escapes && quote != b'`' => i += 2,
+            b if b == quote && bytes.get(i + 1) == Some(&quote) => i += 2,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_statement() {
+        assert_eq!(split_statements("SELECT 1").unwrap(), vec!["SELECT 1"]);
+        assert_eq!(split_statements("  SELECT 1;\n").unwrap(), vec!["SELECT 1"]);
+    }
+
+    #[test]
+    fn test_multiple_statements() {
+        let script = "SET @n = 2;\nSELECT @n;\n\nSELECT 'a;b', \"c;d\", `e;f`;";
+        assert_eq!(split_statements(script).unwrap(), vec!["SET @n = 2", "SELECT @n", "SELECT 'a;b', \"c;d\", `e;f`"]);
+    }
+
+    #[test]
+    fn test_escaped_quotes() {
+        let script = r"SELECT 'it''s; here', 'back\'slash;'; SELECT 2";
+        assert_eq!(split_statements(script).unwrap(), vec![r"SELECT 'it''s; here', 'back\'slash;'", "SELECT 2"]);
+    }
+
+    #[test]
+    fn test_comments() {
+        let script = "-- first; query\nSELECT 1; # trailing;\n/* block; */ SELECT /*+ hint; */ 2;\n-- done\n";
+        assert_eq!(
+            split_statements(script).unwrap(),
+            vec![
+                "-- first; query\nSELECT 1",
+                "# trailing;\n/* block; */ SELECT /*+ hint; */ 2"
+            ]
+        );
+        // Executable comments are run by the server, so they make a statement on their own
+        assert_eq!(
+            split_statements("/*!40101 SET NAMES utf8 */; /*M!100100 SELECT 1 */; /* none */;").unwrap(),
+            vec!["/*!40101 SET NAMES utf8 */", "/*M!100100 SELECT 1 */"]
+        );
+        // `--` without a following space is an operator, not a comment
+        assert_eq!(split_statements("SELECT 1--1; SELECT 2").unwrap(), vec!["SELECT 1--1", "SELECT 2"]);
+    }
+
+    #[test]
+    fn test_delimiter_directive() {
+        let script = "\
+DROP PROCEDURE IF EXISTS report;
+DELIMITER //
+CREATE PROCEDURE report()
+BEGIN
+  SELECT 1;
+  SELECT 2;
+END //
+delimiter ;
+CALL report();
+";
+        assert_eq!(
+            split_statements(script).unwrap(),
+            vec![
+                "DROP PROCEDURE IF EXISTS report",
+                "CREATE PROCEDURE report()\nBEGIN\n  SELECT 1;\n  SELECT 2;\nEND",
+                "CALL report()",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_delimiter_only_at_statement_start() {
+        let script = "SELECT 'x' AS delimiter ; SELECT delimiter FROM t";
+        assert_eq!(split_statements(script).unwrap(), vec!["SELECT 'x' AS delimiter", "SELECT delimiter FROM t"]);
+    }
+
+    #[test]
+    fn test_invalid_delimiter_directive() {
+        let error = split_statements("SELECT 1;\nDELIMITER   \nSELECT 2").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid DELIMITER directive on line 2: missing delimiter");
+        assert!(split_statements("DELIMITER").is_err());
+
+        let error = split_statements("DELIMITER \\\\").unwrap_err();
+        assert!(error.to_string().contains("cannot contain"));
+    }
+
+    #[test]
+    fn test_find_placeholders() {
+        let (sql, placeholders) = find_placeholders("SELECT * FROM t WHERE a = :a AND b IN (:b_1, :a) AND c = ':x'");
+        assert_eq!(sql, "SELECT * FROM t WHERE a = ? AND b IN (?, ?) AND c = ':x'");
+        assert_eq!(
+            placeholders,
+            vec![
+                Placeholder::Named("a".to_string()),
+                Placeholder::Named("b_1".to_string()),
+                Placeholder::Named("a".to_string()),
+            ]
+        );
+
+        let (sql, placeholders) = find_placeholders("SELECT ? /* ? */, `?`, '?' -- ?\n, ?");
+        assert_eq!(sql, "SELECT ? /* ? */, `?`, '?' -- ?\n, ?");
+        assert_eq!(placeholders, vec![Placeholder::Positional(1), Placeholder::Positional(2)]);
+    }
+
+    #[test]
+    fn test_find_placeholders_ignores_labels_and_assignments() {
+        let statement = "retry:LOOP SET @n := @n + 1; END LOOP retry";
+        assert_eq!(find_placeholders(statement), (statement.to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn test_is_read_statement() {
+        for statement in [
+            "SELECT 1",
+            "  /* report */ select * from t",
+            "-- daily\nSHOW TABLES",
+            "(SELECT 1) UNION (SELECT 2)",
+            "WITH recent AS (SELECT * FROM t) SELECT * FROM recent",
+            "EXPLAIN SELECT 1",
+            "WITH a AS (SELECT REPLACE(name,'-','_') AS n FROM t) SELECT n FROM a",
+            "EXPLAIN SELECT INSERT(name,1,2,'ab') FROM t",
+        ] {
+            assert!(is_read_statement(statement), "{}", statement);
+        }
+        for statement in [
+            "UPDATE t SET a = 1",
+            "SET @n = 1",
+            "CALL report()",
+            "WITH old AS (SELECT id FROM t) DELETE FROM t WHERE id IN (SELECT id FROM old)",
+            "EXPLAIN ANALYZE UPDATE t SET a = 1",
+            "/* SELECT */ INSERT INTO t VALUES (1)",
+            "",
+        ] {
+            assert!(!is_read_statement(statement), "{}", statement);
+        }
+        // Keywords inside strings do not count
+        assert!(is_read_statement("WITH a AS (SELECT 'UPDATE' AS x) SELECT x FROM a"));
+    }
+
+    #[test]
+    fn test_check_read_only() {
+        let allowed = [
+            "SELECT * FROM users",
+            "-- report\n(SELECT 1) UNION (SELECT 2)",
+            "show tables",
+            "WITH recent AS (SELECT * FROM t) SELECT * FROM recent",
+            "EXPLAIN SELECT * FROM t",
+            "CALL daily_report()",
+            "SELECT 'DELETE FROM t; DROP TABLE t' AS `INTO OUTFILE`",
+            "SELECT /*+ MAX_EXECUTION_TIME(1000) */ 1",
+            "WITH a AS (SELECT REPLACE(name,'-','_') AS n FROM t) SELECT n FROM a",
+            "EXPLAIN SELECT INSERT(name,1,2,'ab') FROM t",
+            "with a as (select replace /* call */ (name, 'a', 'b') from t) select * from a",
+        ];
+        for statement in allowed {
+            assert!(check_read_only(&[statement.to_string()]).is_ok(), "{}", statement);
+        }
+
+        let refused = [
+            ("UPDATE users SET admin = 1", "statement 1 because UPDATE is not one of"),
+            ("/* SELECT */ DELETE FROM t", "DELETE is not one of"),
+            ("SET SESSION TRANSACTION READ WRITE", "SET is not one of"),
+            ("/*!50000 DROP TABLE t */", "DROP is not one of"),
+            ("WITH old AS (SELECT id FROM t) DELETE FROM t", "WITH ... DELETE changes data"),
+            (
+                "WITH a AS (SELECT REPLACE(x, 'a', 'b') AS y FROM t) REPLACE INTO u SELECT y FROM a",
+                "WITH ... REPLACE changes data",
+            ),
+            ("SELECT * FROM t INTO OUTFILE '/tmp/t.csv'", "INTO OUTFILE writes a file"),
+            ("SELECT 1 /*! ; DELETE FROM t */", "more than one statement"),
+            ("SELECT 1 /*M! ; DELETE FROM t */", "more than one statement"),
+            ("SELECT 1 /*M!100100 ; DELETE FROM t */", "more than one statement"),
+            ("EXPLAIN ANALYZE DELETE FROM t", "EXPLAIN ... DELETE changes data"),
+            ("desc analyze update t set a = 1", "DESC ... UPDATE changes data"),
+            // Under NO_BACKSLASH_ESCAPES the string ends at the second quote
+            (r"SELECT 'a\'; DELETE FROM t; -- '", "more than one statement"),
+        ];
+        for (statement, message) in refused {
+            let error = check_read_only(&["SELECT 1".to_string(), statement.to_string()]).unwrap_err();
+            let message = message.replace("statement 1", "statement 2");
+            assert!(error.to_string().contains(&message), "{}: {}", statement, error);
+            assert!(error.to_string().starts_with("Invalid query"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_no_statements() {
+        for script in ["", "  \n", ";;", "-- nothing here\n/* or here */"] {
+            let error = split_statements(script).unwrap_err();
+            assert_eq!(error.to_string(), "Invalid query: no SQL statements found");
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use std::cell::Cell;
+use std::ffi::OsString;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use anyhow::Result;
+use mysql::{Column, Value};
+
+use crate::OUTPUT_BUFFER_CAPACITY;
+use crate::cli::{Compression, OutputFormat};
+use crate::compression::{CompressedWriter, strip_compression_extension};
+use crate::formats::{WriterOptions, create_restartable_writer};
+use crate::output::{AtomicFile, Destination, register_partial, unregister_partial, with_name_suffix};
+
+/// Limits that end the current part and start the next one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SplitLimits {
+    /// Maximum rows per part
+    pub rows: Option<u64>,
+    /// Maximum bytes per part before compression; a part ends with the row that reaches it
+    pub bytes: Option<u64>,
+}
+
+impl SplitLimits {
+    /// Returns true if either limit is set
+    pub fn is_enabled(&self) -> bool {
+        self.rows.is_some() || self.bytes.is_some()
+    }
+
+    /// Returns true if a part holding `rows` rows and `bytes` bytes is full
+    fn reached(&self, rows: u64, bytes: u64) -> bool {
+        self.rows.is_some_and(|limit| rows >= limit) || self.bytes.is_some_and(|limit| bytes >= limit)
+    }
+}
+
+/// Parses a byte size such as `500000`, `64K`, `100M` or `2G`
+///
+/// Suffixes are case-insensitive powers of 1024 and may end in `B` or `iB` (`100MB`, `1GiB`).
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let digits = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, suffix) = value.split_at(digits);
+    let multiplier: u64 = match suffix.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => return Err(format!("invalid size '{}': expected a number with an optional K, M, G or T suffix", value)),
+    };
+    match number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+    {
+        Some(size) if size > 0 => Ok(size),
+        _ => Err(format!("invalid size '{}': expected a positive number of bytes", value)),
+    }
+}
+
+/// Returns the path of a numbered part, keeping any compression extension last
+///
+/// `out/data.csv.gz` becomes `out/data.part-0001.csv.gz` for part 1.
+pub fn part_path(path: &Path, number: usize) -> PathBuf {
+    with_name_suffix(path, &format!(".part-{:04}", number))
+}
+
+/// Returns the path of the index listing the parts of a split output
+///
+/// `out/data.csv.gz` has its index at `out/data.index.json`.
+pub fn index_path(path: &Path) -> PathBuf {
+    let base = strip_compression_extension(path);
+    let mut name = OsString::from(base.file_stem().unwrap_or_default());
+    name.push(".index.json");
+    path.with_file_name(name)
+}
+
+/// Sink for one part, counting the bytes written to it before compression
+///
+/// The sink has its own buffer, and flushing it only empties the format writer's buffer
+/// into that one: the byte count then covers every row written so far, without a write
+/// to the file per row. The part is flushed in full when it is closed.
+pub struct PartSink {
+    inner: BufWriter<CompressedWriter<Destination>>,
+    path: PathBuf,
+    bytes: Rc<Cell<u64>>,
+}
+
+impl Write for PartSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes.set(self.bytes.get() + written as u64);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A completed part, as recorded in the index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    /// Path of the part file
+    pub path: PathBuf,
+    /// Data rows in the part, not counting the header
+    pub rows: u64,
+    /// Size of the part file on disk
+    pub bytes: u64,
+}
+
+/// Writes one result set as a series of standalone part files plus an index
+///
+/// Every part is a complete file in the output format, with its own header (CSV, TSV) or
+/// `{"data":[...]}` envelope (JSON), so parts can be loaded independently. A new part starts
+/// once the current one reaches [`SplitLimits`]; parts always end on a row boundary and
+/// never hold zero rows unless the whole result is empty, so a part goes over the byte limit
+/// by less than one row.
+///
+/// Each part is renamed into place as soon as it is complete, but stays registered for
+/// removal on an error exit until [`SplitOutput::finish`] writes the index. A failed run
+/// therefore leaves no parts behind.
+pub struct SplitOutput {
+    path: PathBuf,
+    format: OutputFormat,
+    compression: Compression,
+    level: Option<i32>,
+    limits: SplitLimits,
+    parts: Vec<Part>,
+    opened: usize,
+    current_bytes: Rc<Cell<u64>>,
+}
+
+impl SplitOutput {
+    /// Creates a split output whose parts are named after `path`
+    pub fn new(
+        path: &Path,
+        format: OutputFormat,
+        compression: Compression,
+        level: Option<i32>,
+        limits: SplitLimits,
+    ) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            format,
+            compression,
+            level,
+            limits,
+            parts: Vec::new(),
+            opened: 0,
+            current_bytes: Rc::new(Cell::new(0)),
+        }
+    }
+
+    /// Returns the parts completed so far
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    /// Writes the columns and rows, starting a new part whenever a limit is reached
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a part cannot be created, written, or committed.
+    pub fn write<R>(&mut self, columns: &[Column], rows: R, options: &WriterOptions) -> Result<()>
+    where
+        R: Iterator<Item = Vec<Value>>,
+    {
+        let mut writer = create_restartable_writer(self.format, self.open_part()?, options)?;
+        writer.write_columns(columns)?;
+
+        let mut part_rows = 0;
+        for row in rows {
+            if part_rows > 0 && self.limits.reached(part_rows, self.current_bytes.get()) {
+                let next = self.open_part()?;
+                let previous = writer.restart(next)?;
+                self.close_part(previous, part_rows)?;
+                part_rows = 0;
+            }
+            writer.write_values(&row)?;
+            if self.limits.bytes.is_some() {
+                writer.flush()?;
+            }
+            part_rows += 1;
+        }
+
+        let last = writer.into_inner()?;
+        self.close_part(last, part_rows)
+    }
+
+    /// Writes the index and keeps the parts
+    ///
+    /// The index is a JSON document listing every part in order with its row count and size,
+    /// written atomically next to the parts. Returns the index path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index cannot be written.
+    pub fn finish(self) -> Result<PathBuf> {
+        let parts: Vec<_> = self
+            .parts
+            .iter()
+            .map(|part| {
+                serde_json::json!({
+                    "path": part.path.file_name().unwrap_or_default().to_string_lossy(),
+                    "rows": part.rows,
+                    "bytes": part.bytes,
+                })
+            })
+            .collect();
+        let index = serde_json::json!({
+            "format": self.format.as_str(),
+            "compression": self.compression.as_str(),
+            "rows": self.parts.iter().map(|part| part.rows).sum::<u64>(),
+            "parts": parts,
+        });
+
+        let index_path = index_path(&self.path);
+        let mut file =
+            AtomicFile::create(&index_path).map_err(|e| anyhow::anyhow!("Failed to create index file: {}", e))?;
+        serde_json::to_writer_pretty(&mut file, &index)?;
+        writeln!(file)?;
+        file.commit()?;
+
+        for part in &self.parts {
+            unregister_partial(&part.path);
+        }
+        Ok(index_path)
+    }
+
+    /// Opens the next numbered part
+    fn open_part(&mut self) -> Result<PartSink> {
+        // The next part is opened before the previous one is closed
+        self.opened += 1;
+        let path = part_path(&self.path, self.opened);
+        let destination = Destination::open(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to open output part {}: {}", path.display(), e))?;
+        self.current_bytes = Rc::new(Cell::new(0));
+        Ok(PartSink {
+            inner: BufWriter::with_capacity(
+                OUTPUT_BUFFER_CAPACITY,
+                CompressedWriter::new(destination, self.compression, self.level)?,
+            ),
+            path,
+            bytes: Rc::clone(&self.current_bytes),
+        })
+    }
+
+    /// Completes a part and moves it into place
+    fn close_part(&mut self, sink: PartSink, rows: u64) -> Result<()> {
+        let compressed = sink.inner.into_inner().map_err(|e| e.into_error())?;
+        compressed.finish()?.commit()?;
+        register_partial(&sink.path);
+        let bytes = std::fs::metadata(&sink.path)?.len();
+        self.parts.push(Part {
+            path: sink.path,
+            rows,
+            bytes,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mysql::consts::ColumnType;
+
+    use crate::output::PARTIAL_FILES_TEST_LOCK;
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+                .with_name(b"name")
+                .with_character_set(33),
+        ]
+    }
+
+    fn rows(count: i64) -> impl Iterator<Item = Vec<Value>> {
+        (1..=count).map(|id| vec![Value::Int(id), Value::Bytes(format!("name {}", id).into_bytes())])
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1000"), Ok(1000));
+        assert_eq!(parse_size("64K"), Ok(64 * 1024));
+        assert_eq!(parse_size("100mb"), Ok(100 * 1024 * 1024));
+        assert_eq!(parse_size("2GiB"), Ok(2 * 1024 * 1024 * 1024));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("99999999999T").is_err());
+    }
+
+    #[test]
+    fn test_part_and_index_paths() {
+        assert_eq!(part_path(Path::new("out/data.csv"), 1), Path::new("out/data.part-0001.csv"));
+        assert_eq!(part_path(Path::new("out/data.json.gz"), 12), Path::new("out/data.part-0012.json.gz"));
+        assert_eq!(part_path(Path::new("data"), 3), Path::new("data.part-0003"));
+        assert_eq!(index_path(Path::new("out/data.tsv.zst")), Path::new("out/data.index.json"));
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_split_by_rows() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("users.csv");
+        let limits = SplitLimits {
+            rows: Some(2),
+            bytes: None,
+        };
+
+        let mut output = SplitOutput::new(&path, OutputFormat::Csv, Compression::None, None, limits);
+        output.write(&columns(), rows(5), &WriterOptions::default()).unwrap();
+        assert_eq!(output.parts().iter().map(|part| part.rows).collect::<Vec<_>>(), [2, 2, 1]);
+        let index_path = output.finish().unwrap();
+
+        let read = |name: &str| std::fs::read_to_string(directory.path().join(name)).unwrap();
+        assert_eq!(read("users.part-0001.csv"), "id,name\n1,name 1\n2,name 2\n");
+        assert_eq!(read("users.part-0003.csv"), "id,name\n5,name 5\n");
+        assert!(!path.exists());
+
+        let index: serde_json::Value = serde_json::from_str(&read("users.index.json")).unwrap();
+        assert_eq!(index_path, directory.path().join("users.index.json"));
+        assert_eq!(index["rows"], 5);
+        assert_eq!(index["parts"][1]["path"], "users.part-0002.csv");
+        assert_eq!(index["parts"][1]["bytes"], 26);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_split_by_bytes_json_parts_are_standalone() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("users.json");
+        let limits = SplitLimits {
+            rows: None,
+            bytes: Some(1000),
+        };
+
+        let mut output = SplitOutput::new(&path, OutputFormat::Json, Compression::None, None, limits);
+        output.write(&columns(), rows(200), &WriterOptions::default()).unwrap();
+        let parts = output.parts().to_vec();
+        output.finish().unwrap();
+
+        assert!(parts.len() > 2);
+        let mut next_id = 1;
+        for part in &parts {
+            // Parts end on the first row that reaches the limit
+            assert!(part.bytes <= 1000 + 40, "{:?}", part);
+            let document: serde_json::Value = serde_json::from_slice(&std::fs::read(&part.path).unwrap()).unwrap();
+            let data = document["data"].as_array().unwrap();
+            assert_eq!(data.len() as u64, part.rows);
+            assert_eq!(data[0]["id"], next_id);
+            next_id += part.rows;
+        }
+        assert_eq!(next_id, 201);
+    }
+
+    #[test]
+    fn test_split_empty_result_writes_one_part() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("empty.tsv");
+        let limits = SplitLimits {
+            rows: Some(10),
+            bytes: None,
+        };
+
+        let mut output = SplitOutput::new(&path, OutputFormat::Tsv, Compression::None, None, limits);
+        output.write(&columns(), rows(0), &WriterOptions::default()).unwrap();
+        output.finish().unwrap();
+
+        let part = directory.path().join("empty.part-0001.tsv");
+        assert_eq!(std::fs::read_to_string(part).unwrap(), "id\tname\n");
+    }
+
+    #[test]
+    fn test_split_rejects_unsupported_format() {
+        let _guard = PARTIAL_FILES_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("users.md");
+        let limits = SplitLimits {
+            rows: Some(10),
+            bytes: None,
+        };
+
+        let mut output = SplitOutput::new(&path, OutputFormat::Markdown, Compression::None, None, limits);
+        let err = output
+            .write(&columns(), rows(1), &WriterOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot be split"), "{}", err);
+    }
+}
@@ -0,0 +1,295 @@
+use std::fmt::Write as _;
+use std::io::{BufWriter, Write};
+
+use anyhow::Result;
+use mysql::{Column, Value};
+
+use crate::types::{ColumnKind, column_kinds};
+use crate::{FormatWriter, OUTPUT_BUFFER_CAPACITY, mysql_value_to_string};
+
+/// Table name used when none is configured
+pub const DEFAULT_TABLE: &str = "export";
+
+/// Default number of rows in each INSERT statement
+pub const DEFAULT_ROWS_PER_STATEMENT: usize = 100;
+
+/// SQL INSERT dump writer that implements the FormatWriter trait
+///
+/// Writes multi-row `INSERT INTO` statements that can be replayed into another MySQL
+/// instance. Literals are built from the `mysql::Value` of each cell and the column metadata:
+/// numbers are written bare, binary columns (and text that is not valid UTF-8) become hex
+/// literals such as `X'00ff'`, SQL NULL is written as `NULL`, and everything else is a quoted
+/// string escaped like `mysql_real_escape_string`. The output assumes the server's default
+/// `sql_mode`, in which backslash escapes are enabled.
+pub struct SqlWriter<W: Write> {
+    writer: BufWriter<W>,
+    table: String,
+    columns: String,
+    kinds: Vec<ColumnKind>,
+    rows_per_statement: usize,
+    rows_in_statement: usize,
+}
+
+impl<W: Write> SqlWriter<W> {
+    /// Creates a new SqlWriter over the specified writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::with_capacity(OUTPUT_BUFFER_CAPACITY, writer),
+            table: quote_table(DEFAULT_TABLE),
+            columns: String::new(),
+            kinds: Vec::new(),
+            rows_per_statement: DEFAULT_ROWS_PER_STATEMENT,
+            rows_in_statement: 0,
+        }
+    }
+
+    /// Sets the target table, optionally qualified with a database (`db.table`)
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.table = quote_table(table);
+        self
+    }
+
+    /// Sets the maximum number of rows in each INSERT statement
+    pub fn with_rows_per_statement(mut self, rows: usize) -> Self {
+        self.rows_per_statement = rows.max(1);
+        self
+    }
+
+    /// Writes one row of already-encoded literals, starting or ending statements as needed
+    fn write_literals(&mut self, literals: &str) -> Result<()> {
+        if self.rows_in_statement == 0 {
+            write!(self.writer, "INSERT INTO {} ({}) VALUES\n({})", self.table, self.columns, literals)?;
+        } else {
+            write!(self.writer, ",\n({})", literals)?;
+        }
+
+        self.rows_in_statement += 1;
+        if self.rows_in_statement == self.rows_per_statement {
+            self.end_statement()?;
+        }
+        Ok(())
+    }
+
+    fn end_statement(&mut self) -> Result<()> {
+        if self.rows_in_statement > 0 {
+            writeln!(self.writer, ";")?;
+            self.rows_in_statement = 0;
+        }
+        Ok(())
+    }
+
+    fn kind(&self, index: usize) -> ColumnKind {
+        self.kinds.get(index).copied().unwrap_or(ColumnKind::Text)
+    }
+}
+
+impl<W: Write> FormatWriter for SqlWriter<W> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        self.columns = columns
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        let mut literals = String::new();
+        for (index, field) in row.iter().enumerate() {
+            if index > 0 {
+                literals.push_str(", ");
+            }
+            push_string_literal(&mut literals, field);
+        }
+        self.write_literals(&literals)
+    }
+
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        self.kinds = column_kinds(columns);
+        self.write_header(&crate::column_names(columns))
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        let mut literals = String::new();
+        for (index, value) in values.iter().enumerate() {
+            if index > 0 {
+                literals.push_str(", ");
+            }
+            push_literal(&mut literals, self.kind(index), value)?;
+        }
+        self.write_literals(&literals)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.end_statement()?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Appends the SQL literal for a value according to its column kind
+fn push_literal(out: &mut String, kind: ColumnKind, value: &Value) -> Result<()> {
+    match value {
+        Value::NULL => out.push_str("NULL"),
+        Value::Bytes(bytes) if kind == ColumnKind::Binary => push_hex_literal(out, bytes),
+        Value::Bytes(bytes) => match std::str::from_utf8(bytes) {
+            Ok(text) if kind.is_numeric() && is_numeric_literal(text) => out.push_str(text),
+            Ok(text) => push_string_literal(out, text),
+            // Text that is not valid UTF-8 is kept byte-for-byte as a hex literal
+            Err(_) => push_hex_literal(out, bytes),
+        },
+        Value::Int(i) => write!(out, "{}", i)?,
+        Value::UInt(u) => write!(out, "{}", u)?,
+        Value::Float(f) if f.is_finite() => write!(out, "{}", f)?,
+        Value::Double(d) if d.is_finite() => write!(out, "{}", d)?,
+        _ => push_string_literal(out, &mysql_value_to_string(value)?),
+    }
+    Ok(())
+}
+
+/// Appends a single-quoted string literal, escaping it like `mysql_real_escape_string`
+fn push_string_literal(out: &mut String, text: &str) {
+    out.reserve(text.len() + 2);
+    out.push('\'');
+    for ch in text.chars() {
+        match ch {
+            '\0' => out.push_str("\\0"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\u{1a}' => out.push_str("\\Z"),
+            '\\' => out.push_str("\\\\"),
+            '\'' => out.push_str("\\'"),
+            '"' => out.push_str("\\\""),
+            _ => out.push(ch),
+        }
+    }
+    out.push('\'');
+}
+
+/// Appends a hex literal (`X'...'`) for raw bytes
+fn push_hex_literal(out: &mut String, bytes: &[u8]) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    out.reserve(bytes.len() * 2 + 3);
+    out.push_str("X'");
+    for byte in bytes {
+        out.push(HEX[usize::from(byte >> 4)] as char);
+        out.push(HEX[usize::from(byte & 0x0f)] as char);
+    }
+    out.push('\'');
+}
+
+/// Returns true if text from a numeric column can be written as a bare literal
+fn is_numeric_literal(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    digits.starts_with(|c: char| c.is_ascii_digit()) && digits.parse::<f64>().is_ok()
+}
+
+/// Quotes an identifier with backticks, doubling any embedded backtick
+fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// Quotes a table name, treating a `.` as the separator between database and table
+fn quote_table(table: &str) -> String {
+    table.split('.').map(quote_identifier).collect::<Vec<_>>().join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mysql::consts::{ColumnFlags, ColumnType};
+
+    fn literal(kind: ColumnKind, value: Value) -> String {
+        let mut out = String::new();
+        push_literal(&mut out, kind, &value).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_sql_batched_inserts() {
+        let columns = [
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+                .with_name(b"name")
+                .with_character_set(33),
+            Column::new(ColumnType::MYSQL_TYPE_BLOB)
+                .with_name(b"data")
+                .with_character_set(63)
+                .with_flags(ColumnFlags::BINARY_FLAG),
+        ];
+
+        let mut output = Vec::new();
+        let mut writer = SqlWriter::new(&mut output)
+            .with_table("shop.orders")
+            .with_rows_per_statement(2);
+        writer.write_columns(&columns).unwrap();
+        writer
+            .write_values(&[
+                Value::Bytes(b"1".to_vec()),
+                Value::Bytes(b"O'Brien".to_vec()),
+                Value::Bytes(vec![0, 255]),
+            ])
+            .unwrap();
+        writer
+            .write_values(&[Value::Int(2), Value::NULL, Value::Bytes(Vec::new())])
+            .unwrap();
+        writer
+            .write_values(&[Value::Int(3), Value::Bytes(b"".to_vec()), Value::NULL])
+            .unwrap();
+        writer.finalize().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "INSERT INTO `shop`.`orders` (`id`, `name`, `data`) VALUES\n\
+             (1, 'O\\'Brien', X'00ff'),\n\
+             (2, NULL, X'');\n\
+             INSERT INTO `shop`.`orders` (`id`, `name`, `data`) VALUES\n\
+             (3, '', NULL);\n"
+        );
+    }
+
+    #[test]
+    fn test_sql_empty_result_writes_nothing() {
+        let mut output = Vec::new();
+        let mut writer = SqlWriter::new(&mut output);
+        writer.write_header(&["id".to_string()]).unwrap();
+        writer.finalize().unwrap();
+
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_string_escaping() {
+        assert_eq!(
+            literal(ColumnKind::Text, Value::Bytes("a\\b\n\r\0\u{1a}\"'é".as_bytes().to_vec())),
+            "'a\\\\b\\n\\r\\0\\Z\\\"\\'é'"
+        );
+        assert_eq!(literal(ColumnKind::Text, Value::Bytes(vec![0xff, 0x41])), "X'ff41'");
+    }
+
+    #[test]
+    fn test_numeric_literals() {
+        assert_eq!(literal(ColumnKind::Decimal, Value::Bytes(b"-12.50".to_vec())), "-12.50");
+        assert_eq!(literal(ColumnKind::Float, Value::Double(1.5)), "1.5");
+        assert_eq!(literal(ColumnKind::UnsignedInteger, Value::UInt(u64::MAX)), "18446744073709551615");
+        assert_eq!(literal(ColumnKind::Integer, Value::Bytes(b"1; DROP".to_vec())), "'1; DROP'");
+        assert_eq!(literal(ColumnKind::Integer, Value::Bytes(b"1-2".to_vec())), "'1-2'");
+        assert_eq!(literal(ColumnKind::Text, Value::Bytes(b"00501".to_vec())), "'00501'");
+    }
+
+    #[test]
+    fn test_temporal_literals() {
+        assert_eq!(
+            literal(ColumnKind::DateTime, Value::Date(2024, 1, 15, 10, 30, 0, 0)),
+            "'2024-01-15 10:30:00.000000'"
+        );
+        assert_eq!(literal(ColumnKind::Date, Value::Bytes(b"2024-01-15".to_vec())), "'2024-01-15'");
+    }
+
+    #[test]
+    fn test_quote_identifiers() {
+        assert_eq!(quote_identifier("we`ird"), "`we``ird`");
+        assert_eq!(quote_table("db.table"), "`db`.`table`");
+    }
+}
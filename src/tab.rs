@@ -1,6 +1,7 @@
 use std::io::Write;
 
-use csv::{QuoteStyle, WriterBuilder};
+use crate::FormatWriter;
+use crate::csv::CsvWriter;
 
 /// Writes rows to a tab-delimited output using the provided writer.
 ///
@@ -18,18 +19,7 @@ where
     F: IntoIterator<Item = String>,
     W: Write,
 {
-    let mut wtr = WriterBuilder::new()
-        .delimiter(b'\t')
-        .quote_style(QuoteStyle::Necessary)
-        .buffer_capacity(8 * 1024) // 8KB buffer for better performance
-        .from_writer(output);
-
-    for row in rows {
-        wtr.write_record(row)?;
-    }
-
-    wtr.flush()?; // Ensure all data is written
-    Ok(())
+    write_bytes(rows, output)
 }
 
 /// Writes rows to a tab-delimited output using the provided writer with generic field types.
@@ -52,14 +42,57 @@ where
     T: AsRef<[u8]>,
     W: Write,
 {
-    let mut wtr = WriterBuilder::new()
-        .delimiter(b'\t')
-        .quote_style(QuoteStyle::Necessary)
-        .from_writer(output);
+    let mut writer = CsvWriter::tsv(output);
 
     for row in rows {
-        wtr.write_record(row)?;
+        writer.write_record(row)?;
+    }
+
+    writer.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RestartableWriter;
+    use mysql::Value;
+
+    #[test]
+    fn test_tsv_format() {
+        let mut output = Vec::new();
+        let mut writer = CsvWriter::tsv(&mut output);
+
+        writer.write_header(&["id".to_string(), "note".to_string()]).unwrap();
+        writer.write_row(&["1".to_string(), "tab\there".to_string()]).unwrap();
+        writer.finalize().unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, "id\tnote\n1\t\"tab\there\"\n");
     }
 
-    Ok(())
+    #[test]
+    fn test_tsv_null_marker() {
+        let mut output = Vec::new();
+        let mut writer = CsvWriter::tsv(&mut output).with_null_marker("NULL");
+
+        writer
+            .write_values(&[Value::NULL, Value::Bytes(Vec::new()), Value::Bytes(b"x".to_vec())])
+            .unwrap();
+        writer.finalize().unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "NULL\t\tx\n");
+    }
+
+    #[test]
+    fn test_tsv_restart_repeats_header() {
+        let mut writer = CsvWriter::tsv(Vec::new());
+        writer.write_header(&["id".to_string()]).unwrap();
+        writer.write_row(&["1".to_string()]).unwrap();
+
+        let first = writer.restart(Vec::new()).unwrap();
+        writer.write_row(&["2".to_string()]).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(String::from_utf8(first).unwrap(), "id\n1\n");
+    }
 }
@@ -0,0 +1,342 @@
+use std::fmt;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+
+use anyhow::Result;
+use mysql::{Column, Value};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::types::column_kinds;
+use crate::{FormatWriter, OUTPUT_BUFFER_CAPACITY, mysql_value_to_string};
+
+/// Maximum display width of table cells, for one named column or for every column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WidthLimit {
+    /// Column the limit applies to, or `None` for every column
+    pub column: Option<String>,
+    /// Maximum width in terminal cells, including the truncation marker
+    pub width: usize,
+}
+
+impl FromStr for WidthLimit {
+    type Err = String;
+
+    /// Parses `WIDTH` (every column) or `COLUMN=WIDTH` (one column)
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (column, width) = match value.rsplit_once('=') {
+            Some((column, width)) if !column.is_empty() => (Some(column.to_string()), width),
+            Some(_) => return Err(format!("missing column name in '{}'", value)),
+            None => (None, value),
+        };
+        match width.trim().parse::<usize>() {
+            Ok(width) if width > 0 => Ok(Self { column, width }),
+            _ => Err(format!("invalid width '{}': expected a positive number of characters", width)),
+        }
+    }
+}
+
+impl fmt::Display for WidthLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "{}={}", column, self.width),
+            None => write!(f, "{}", self.width),
+        }
+    }
+}
+
+/// Aligned, box-drawn table writer that implements the FormatWriter trait
+///
+/// Produces output similar to the mysql client's interactive tables. Column widths are
+/// measured in terminal cells, so wide (CJK) and zero-width characters line up, and
+/// numeric columns are right-aligned when column metadata is available. Control
+/// characters are shown escaped (`\n`, `\t`) to keep each row on one line, and cells wider
+/// than their [`WidthLimit`] are cut and end in `…`.
+///
+/// Column widths depend on every row, so rows are held in memory until
+/// [`FormatWriter::finish`]. This format is meant for reading results in a terminal;
+/// prefer a file format for large exports.
+pub struct TableWriter<W: Write> {
+    writer: BufWriter<W>,
+    null_marker: String,
+    limits: Vec<WidthLimit>,
+    columns: Vec<String>,
+    column_limits: Vec<Option<usize>>,
+    right_align: Vec<bool>,
+    rows: Vec<Vec<String>>,
+}
+
+impl<W: Write> TableWriter<W> {
+    /// Creates a new TableWriter over the specified writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::with_capacity(OUTPUT_BUFFER_CAPACITY, writer),
+            null_marker: "NULL".to_string(),
+            limits: Vec::new(),
+            columns: Vec::new(),
+            column_limits: Vec::new(),
+            right_align: Vec::new(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Sets the text shown for SQL NULL values (`NULL` by default)
+    pub fn with_null_marker(mut self, null_marker: impl Into<String>) -> Self {
+        self.null_marker = null_marker.into();
+        self
+    }
+
+    /// Sets cell width limits; a limit for a named column overrides one for every column
+    pub fn with_width_limits(mut self, limits: impl IntoIterator<Item = WidthLimit>) -> Self {
+        self.limits = limits.into_iter().collect();
+        self
+    }
+
+    /// Resolves the width limit for a column, letting later limits win
+    fn limit_for(&self, column: &str) -> Option<usize> {
+        let named = self
+            .limits
+            .iter()
+            .rev()
+            .find(|limit| limit.column.as_deref() == Some(column));
+        named
+            .or_else(|| self.limits.iter().rev().find(|limit| limit.column.is_none()))
+            .map(|limit| limit.width)
+    }
+
+    /// Escapes and truncates a cell for display in the given column
+    fn cell(&self, index: usize, text: &str) -> String {
+        truncate(escape_controls(text), self.column_limits.get(index).copied().flatten())
+    }
+
+    /// Writes a border line from the left, middle, and right box-drawing characters
+    fn write_border(&mut self, widths: &[usize], left: char, middle: char, right: char) -> Result<()> {
+        let mut line = String::new();
+        line.push(left);
+        for (index, width) in widths.iter().enumerate() {
+            if index > 0 {
+                line.push(middle);
+            }
+            line.extend(std::iter::repeat_n('─', width + 2));
+        }
+        line.push(right);
+        writeln!(self.writer, "{}", line)?;
+        Ok(())
+    }
+
+    /// Writes one row of cells padded to the column widths
+    fn write_line(&mut self, widths: &[usize], cells: &[String], align: bool) -> Result<()> {
+        let mut line = String::from("│");
+        for (index, width) in widths.iter().enumerate() {
+            let cell = cells.get(index).map(String::as_str).unwrap_or("");
+            let padding = " ".repeat(width.saturating_sub(cell.width()));
+            let right = align && self.right_align.get(index).copied().unwrap_or(false);
+            line.push(' ');
+            if right {
+                line.push_str(&padding);
+                line.push_str(cell);
+            } else {
+                line.push_str(cell);
+                line.push_str(&padding);
+            }
+            line.push_str(" │");
+        }
+        writeln!(self.writer, "{}", line)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> FormatWriter for TableWriter<W> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        self.column_limits = columns.iter().map(|column| self.limit_for(column)).collect();
+        self.columns = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| self.cell(index, column))
+            .collect();
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        let cells = row
+            .iter()
+            .enumerate()
+            .map(|(index, field)| self.cell(index, field))
+            .collect();
+        self.rows.push(cells);
+        Ok(())
+    }
+
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        self.right_align = column_kinds(columns)
+            .into_iter()
+            .map(|kind| kind.is_numeric())
+            .collect();
+        self.write_header(&crate::column_names(columns))
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        let mut cells = Vec::with_capacity(values.len());
+        for (index, value) in values.iter().enumerate() {
+            match value {
+                Value::NULL => cells.push(self.cell(index, &self.null_marker)),
+                _ => cells.push(self.cell(index, &mysql_value_to_string(value)?)),
+            }
+        }
+        self.rows.push(cells);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if !self.columns.is_empty() {
+            let mut widths: Vec<usize> = self.columns.iter().map(|column| column.width()).collect();
+            for row in &self.rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.width());
+                }
+            }
+
+            let columns = std::mem::take(&mut self.columns);
+            let rows = std::mem::take(&mut self.rows);
+            self.write_border(&widths, '┌', '┬', '┐')?;
+            self.write_line(&widths, &columns, false)?;
+            self.write_border(&widths, '├', '┼', '┤')?;
+            for row in &rows {
+                self.write_line(&widths, row, true)?;
+            }
+            self.write_border(&widths, '└', '┴', '┘')?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Replaces control characters with their escaped form so a cell stays on one line
+fn escape_controls(text: &str) -> String {
+    if !text.chars().any(char::is_control) {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len() + 8);
+    for ch in text.chars() {
+        match ch {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            _ if ch.is_control() => out.extend(ch.escape_default()),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Cuts text to at most `limit` terminal cells, ending it with `…` when shortened
+fn truncate(text: String, limit: Option<usize>) -> String {
+    let Some(limit) = limit else {
+        return text;
+    };
+    if text.width() <= limit {
+        return text;
+    }
+
+    let mut out = String::new();
+    let mut width = 0;
+    for ch in text.chars() {
+        let char_width = ch.width().unwrap_or(0);
+        if width + char_width >= limit {
+            break;
+        }
+        width += char_width;
+        out.push(ch);
+    }
+    out.push('…');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mysql::consts::ColumnType;
+
+    fn render(writer_limits: &[WidthLimit], rows: &[Vec<Value>]) -> String {
+        let columns = [
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+                .with_name(b"name")
+                .with_character_set(33),
+        ];
+
+        let mut output = Vec::new();
+        let mut writer = TableWriter::new(&mut output).with_width_limits(writer_limits.to_vec());
+        writer.write_columns(&columns).unwrap();
+        for row in rows {
+            writer.write_values(row).unwrap();
+        }
+        writer.finalize().unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_table_alignment_with_wide_characters() {
+        let table = render(
+            &[],
+            &[
+                vec![Value::Int(1), Value::Bytes("名前".as_bytes().to_vec())],
+                vec![Value::Int(200), Value::NULL],
+                vec![Value::Int(3), Value::Bytes(b"a\tb\nc".to_vec())],
+            ],
+        );
+
+        assert_eq!(
+            table,
+            "┌─────┬─────────┐\n\
+             │ id  │ name    │\n\
+             ├─────┼─────────┤\n\
+             │   1 │ 名前    │\n\
+             │ 200 │ NULL    │\n\
+             │   3 │ a\\tb\\nc │\n\
+             └─────┴─────────┘\n"
+        );
+    }
+
+    #[test]
+    fn test_table_truncation_per_column() {
+        let limits = ["3".parse().unwrap(), "name=5".parse().unwrap()];
+        let table = render(
+            &limits,
+            &[vec![
+                Value::Int(123456),
+                Value::Bytes("日本語テキスト".as_bytes().to_vec()),
+            ]],
+        );
+
+        assert!(table.contains("│ 12… │ 日本… │\n"), "{}", table);
+        assert!(table.contains("│ id  │ name  │\n"), "{}", table);
+    }
+
+    #[test]
+    fn test_table_empty_result() {
+        assert_eq!(render(&[], &[]), "┌────┬──────┐\n│ id │ name │\n├────┼──────┤\n└────┴──────┘\n");
+
+        let mut output = Vec::new();
+        let mut writer = TableWriter::new(&mut output);
+        writer.write_header(&[]).unwrap();
+        writer.finalize().unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_width_limit_parsing() {
+        assert_eq!(
+            "40".parse::<WidthLimit>().unwrap(),
+            WidthLimit {
+                column: None,
+                width: 40
+            }
+        );
+        let limit: WidthLimit = "a=b=12".parse().unwrap();
+        assert_eq!(limit.column.as_deref(), Some("a=b"));
+        assert_eq!(limit.to_string(), "a=b=12");
+        assert!("0".parse::<WidthLimit>().is_err());
+        assert!("=5".parse::<WidthLimit>().is_err());
+        assert!("name=wide".parse::<WidthLimit>().is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, Utc};
+
+use crate::cli::OutputTimezone;
+use crate::params::QueryParams;
+use crate::partition::escape_path_segment;
+
+/// Format of `{date}` when none is given
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Format of `{datetime}` when none is given, free of characters Windows rejects in file names
+pub const DEFAULT_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// Values available to output path placeholders
+pub struct TemplateValues<'a> {
+    /// Time the run started, shared by every time placeholder
+    pub now: DateTime<Utc>,
+    /// Time zone the date and time placeholders are shown in
+    pub timezone: OutputTimezone,
+    /// Query file, whose name is `{query_name}`
+    pub query_file: Option<&'a Path>,
+    /// Query parameters, available as `{param.NAME}`
+    pub params: &'a QueryParams,
+}
+
+/// Returns true if a path contains placeholders or escaped braces to expand
+pub fn is_template(path: &Path) -> bool {
+    path.to_string_lossy().contains(['{', '}'])
+}
+
+/// Expands the placeholders of an output path template
+///
+/// | Placeholder | Value |
+/// |-------------|-------|
+/// | `{date}`, `{date:FORMAT}` | Start date, `2026-10-17` by default |
+/// | `{datetime}`, `{datetime:FORMAT}` | Start time, `20261017T093000` by default |
+/// | `{epoch}` | Start time in seconds since the Unix epoch |
+/// | `{query_name}` | File name of `--query-file` without its extension |
+/// | `{param.NAME}` | Value of the query parameter `NAME` |
+/// | `{hostname}` | Name of the machine running the export |
+///
+/// `FORMAT` is a strftime-style format such as `%Y/%m/%d`, so a `/` in it creates
+/// directories. Parameter values are percent-encoded the way partition values are, with `.`
+/// and `..` encoded too, so they cannot add directories or leave the output directory. `{{`
+/// and `}}` stand for literal braces.
+///
+/// # Errors
+///
+/// Returns an error for unknown or malformed placeholders, for placeholders without a value,
+/// such as `{query_name}` for a query given with `--query`, and for empty parameter values,
+/// which would drop a directory from the path.
+pub fn expand_template(template: &str, values: &TemplateValues) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        out.push_str(&rest[..pos]);
+        let brace = &rest[pos..pos + 1];
+        if rest[pos + 1..].starts_with(brace) {
+            out.push_str(brace);
+            rest = &rest[pos + 2..];
+            continue;
+        }
+        if brace == "}" {
+            bail!("Invalid output template '{}': unmatched '}}' (write '}}}}' for a literal brace)", template);
+        }
+        let Some(len) = rest[pos + 1..].find('}') else {
+            bail!("Invalid output template '{}': unclosed '{{' (write '{{{{' for a literal brace)", template);
+        };
+        let placeholder = &rest[pos + 1..pos + 1 + len];
+        let value = expand_placeholder(placeholder, values)
+            .map_err(|e| anyhow::anyhow!("Invalid output template '{}': {}", template, e))?;
+        out.push_str(&value);
+        rest = &rest[pos + len + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Returns the value of one placeholder, given without its braces
+fn expand_placeholder(placeholder: &str, values: &TemplateValues) -> Result<String> {
+    let (name, format) = match placeholder.split_once(':') {
+        Some((name, format)) => (name, Some(format)),
+        None => (placeholder, None),
+    };
+    let value = match name {
+        "date" => format_time(values, format.unwrap_or(DEFAULT_DATE_FORMAT))?,
+        "datetime" => format_time(values, format.unwrap_or(DEFAULT_DATETIME_FORMAT))?,
+        _ if format.is_some() => bail!("{{{}}} does not take a format", name),
+        "epoch" => values.now.timestamp().to_string(),
+        "query_name" => match values.query_file.and_then(Path::file_stem) {
+            Some(stem) => stem.to_string_lossy().into_owned(),
+            None => bail!("{{query_name}} needs the query to come from --query-file"),
+        },
+        "hostname" => hostname()?,
+        _ => match name.strip_prefix("param.") {
+            Some(param) => match values.params.get(param) {
+                Some(value) => path_segment(name, &crate::mysql_value_to_string(value)?)?,
+                None => bail!("{{{}}} has no value: pass --param {}=VALUE or add it to --params-file", name, param),
+            },
+            None => bail!(
+                "unknown placeholder {{{}}} (expected date, datetime, epoch, query_name, param.NAME or hostname)",
+                name
+            ),
+        },
+    };
+    Ok(value)
+}
+
+/// Percent-encodes a placeholder value so it stays within one path segment
+///
+/// `.` and `..` are encoded as well, since as a whole segment they would name the current or
+/// parent directory.
+fn path_segment(name: &str, value: &str) -> Result<String> {
+    match value {
+        "" => bail!("{{{}}} is empty, which would drop a directory from the path", name),
+        "." => Ok("%2E".to_string()),
+        ".." => Ok("%2E%2E".to_string()),
+        _ => Ok(escape_path_segment(value)),
+    }
+}
+
+/// Formats the start time with a strftime-style format in the selected time zone
+fn format_time(values: &TemplateValues, format: &str) -> Result<String> {
+    // An invalid specifier yields `Item::Error` forever, so stop at the first one
+    let items: Option<Vec<Item>> = StrftimeItems::new(format)
+        .map(|item| (item != Item::Error).then_some(item))
+        .collect();
+    let Some(items) = items else {
+        bail!("'{}' is not a valid strftime format", format);
+    };
+    let formatted = match values.timezone {
+        OutputTimezone::Local => values
+            .now
+            .with_timezone(&Local)
+            .format_with_items(items.iter())
+            .to_string(),
+        OutputTimezone::Utc => values.now.format_with_items(items.iter()).to_string(),
+    };
+    Ok(formatted)
+}
+
+/// Returns the name of this machine
+///
+/// Uses the `HOSTNAME` or `COMPUTERNAME` environment variable when set, then the kernel's
+/// host name on Linux, then the `hostname` command.
+fn hostname() -> Result<String> {
+    let from_env = ["HOSTNAME", "COMPUTERNAME"]
+        .into_iter()
+        .find_map(|name| std::env::var(name).ok());
+    let name = from_env
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .or_else(|| {
+            std::process::Command::new("hostname")
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+        })
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    match name {
+        Some(name) => path_segment("hostname", &name),
+        None => bail!("{{hostname}} could not be determined; set the HOSTNAME environment variable"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::QueryParam;
+    use chrono::TimeZone;
+
+    fn expand(template: &str, query_file: Option<&Path>, params: &[&str]) -> Result<String> {
+        let params: Vec<QueryParam> = params.iter().map(|param| param.parse().unwrap()).collect();
+        let values = TemplateValues {
+            now: Utc.with_ymd_and_hms(2026, 10, 17, 9, 30, 5).unwrap(),
+            timezone: OutputTimezone::Utc,
+            query_file,
+            params: &QueryParams::load(None, &params).unwrap(),
+        };
+        expand_template(template, &values)
+    }
+
+    #[test]
+    fn test_time_placeholders() {
+        assert_eq!(
+            expand("/app/output/daily-audit-{date}.json", None, &[]).unwrap(),
+            "/app/output/daily-audit-2026-10-17.json"
+        );
+        assert_eq!(expand("out-{datetime}.csv", None, &[]).unwrap(), "out-20261017T093005.csv");
+        assert_eq!(expand("out-{epoch}.csv", None, &[]).unwrap(), "out-1792229405.csv");
+        assert_eq!(expand("{date:%Y/%m}/{datetime:%H%M}.csv", None, &[]).unwrap(), "2026/10/0930.csv");
+    }
+
+    #[test]
+    fn test_query_and_param_placeholders() {
+        let query_file = Path::new("reports/daily_audit.sql");
+        assert_eq!(expand("{query_name}.json", Some(query_file), &[]).unwrap(), "daily_audit.json");
+
+        let path = expand("{param.region}/{param.day}.csv", None, &["region=eu/west", "day:date=2026-10-01"]).unwrap();
+        assert_eq!(path, "eu%2Fwest/2026-10-01.csv");
+
+        // Values cannot name the current or parent directory, or drop a directory
+        assert_eq!(expand("out/{param.region}/x.csv", None, &["region=.."]).unwrap(), "out/%2E%2E/x.csv");
+        assert_eq!(expand("out/{param.region}/x.csv", None, &["region=."]).unwrap(), "out/%2E/x.csv");
+        assert_eq!(expand("out/{param.region}/x.csv", None, &["region=..."]).unwrap(), "out/.../x.csv");
+        let error = expand("out/{param.region}/x.csv", None, &["region="]).unwrap_err();
+        assert!(error.to_string().contains("{param.region} is empty"), "{}", error);
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        assert_eq!(expand("{{date}}-{date}}}.csv", None, &[]).unwrap(), "{date}-2026-10-17}.csv");
+        assert!(!is_template(Path::new("out/data.csv")));
+        assert!(is_template(Path::new("out/{date}.csv")));
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        let cases = [
+            ("{unknown}.csv", "unknown placeholder {unknown}"),
+            ("{date.csv", "unclosed '{'"),
+            ("date}.csv", "unmatched '}'"),
+            ("{date:%Q}.csv", "not a valid strftime format"),
+            ("{epoch:%s}.csv", "{epoch} does not take a format"),
+            ("{query_name}.csv", "needs the query to come from --query-file"),
+            ("{param.region}.csv", "{param.region} has no value"),
+        ];
+        for (template, message) in cases {
+            let error = expand(template, None, &[]).unwrap_err().to_string();
+            assert!(error.starts_with("Invalid output template"), "{}", error);
+            assert!(error.contains(message), "{}: {}", template, error);
+        }
+    }
+
+    #[test]
+    fn test_hostname_from_env() {
+        temp_env::with_var("HOSTNAME", Some("db-runner-1"), || {
+            assert_eq!(expand("{hostname}.csv", None, &[]).unwrap(), "db-runner-1.csv");
+        });
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::{Result, anyhow};
+use mysql::Value;
+
+use crate::mysql_value_to_string;
+
+/// Microseconds in one second
+pub(crate) const MICROS_PER_SECOND: i64 = 1_000_000;
+
+/// Microseconds in one day
+pub(crate) const MICROS_PER_DAY: i64 = 86_400 * MICROS_PER_SECOND;
+
+/// Splits a DATE/DATETIME value into days since the Unix epoch and microseconds into the day
+///
+/// Returns `None` for MySQL zero dates (a zero year, month or day).
+pub(crate) fn temporal_parts(value: &Value) -> Result<Option<(i64, i64)>> {
+    let (year, month, day, hour, minute, second, micros) = match value {
+        Value::Date(year, month, day, hour, minute, second, micros) => (
+            u32::from(*year),
+            u32::from(*month),
+            u32::from(*day),
+            i64::from(*hour),
+            i64::from(*minute),
+            i64::from(*second),
+            i64::from(*micros),
+        ),
+        _ => parse_datetime(&mysql_value_to_string(value)?)?,
+    };
+
+    if year == 0 || month == 0 || day == 0 {
+        return Ok(None);
+    }
+
+    let seconds = hour * 3600 + minute * 60 + second;
+    Ok(Some((days_from_civil(year, month, day), seconds * MICROS_PER_SECOND + micros)))
+}
+
+/// Parses `YYYY-MM-DD[ HH:MM:SS[.ffffff]]` as returned by the text protocol
+fn parse_datetime(text: &str) -> Result<(u32, u32, u32, i64, i64, i64, i64)> {
+    let invalid = || anyhow!("Type conversion error: invalid date/time '{}'", text);
+    let number = |part: &str| part.parse::<u32>().map_err(|_| invalid());
+
+    let (date, time) = text.trim().split_once([' ', 'T']).unwrap_or((text.trim(), ""));
+    let mut date_parts = date.splitn(3, '-');
+    let year = number(date_parts.next().ok_or_else(invalid)?)?;
+    let month = number(date_parts.next().ok_or_else(invalid)?)?;
+    let day = number(date_parts.next().ok_or_else(invalid)?)?;
+    if month > 12 || day > 31 {
+        return Err(invalid());
+    }
+
+    if time.is_empty() {
+        return Ok((year, month, day, 0, 0, 0, 0));
+    }
+
+    let (clock, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut clock_parts = clock.splitn(3, ':');
+    let hour = number(clock_parts.next().ok_or_else(invalid)?)?;
+    let minute = number(clock_parts.next().ok_or_else(invalid)?)?;
+    let second = number(clock_parts.next().ok_or_else(invalid)?)?;
+    if hour > 23 || minute > 59 || second > 59 || fraction.len() > 6 {
+        return Err(invalid());
+    }
+    let micros = if fraction.is_empty() {
+        0
+    } else {
+        number(fraction)? * 10u32.pow(6 - fraction.len() as u32)
+    };
+
+    Ok((year, month, day, i64::from(hour), i64::from(minute), i64::from(second), i64::from(micros)))
+}
+
+/// Returns the number of days from 1970-01-01 to the given proleptic Gregorian date
+fn days_from_civil(year: u32, month: u32, day: u32) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_from_civil() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+    }
+
+    #[test]
+    fn test_temporal_parts() {
+        assert_eq!(
+            temporal_parts(&Value::Bytes(b"2024-02-29 12:30:45.25".to_vec())).unwrap(),
+            Some((19782, 45_045_250_000))
+        );
+        assert_eq!(temporal_parts(&Value::Date(1970, 1, 2, 0, 0, 1, 0)).unwrap(), Some((1, 1_000_000)));
+        assert_eq!(temporal_parts(&Value::Bytes(b"0000-00-00 00:00:00".to_vec())).unwrap(), None);
+        assert!(temporal_parts(&Value::Bytes(b"2024-13-01".to_vec())).is_err());
+    }
+}
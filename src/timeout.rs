@@ -0,0 +1,181 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use mysql::Pool;
+use mysql::prelude::Queryable;
+
+/// Parses a duration such as `30`, `30s`, `1500ms`, `5m` or `2h`
+///
+/// A number without a suffix is a number of seconds. Suffixes are case-insensitive.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let digits = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, suffix) = value.split_at(digits);
+    let millis_per_unit: u64 = match suffix.to_ascii_lowercase().as_str() {
+        "ms" => 1,
+        "" | "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => {
+            return Err(format!(
+                "invalid duration '{}': expected a number with an optional ms, s, m or h suffix",
+                value
+            ));
+        },
+    };
+    match number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(millis_per_unit))
+    {
+        Some(millis) if millis > 0 => Ok(Duration::from_millis(millis)),
+        _ => Err(format!("invalid duration '{}': expected a positive length of time", value)),
+    }
+}
+
+/// Formats a duration the way [`parse_duration`] reads it, as whole seconds when possible
+pub fn format_duration(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+/// Server family, which decides the variable that limits statement execution time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerFlavor {
+    /// MySQL, limited by `max_execution_time` in milliseconds (SELECT statements only)
+    MySql,
+    /// MariaDB, limited by `max_statement_time` in seconds (every statement)
+    MariaDb,
+}
+
+impl ServerFlavor {
+    /// Tells the server family from the result of `SELECT VERSION()`, e.g. `10.11.6-MariaDB`
+    pub fn from_version(version: &str) -> Self {
+        if version.to_ascii_lowercase().contains("mariadb") {
+            Self::MariaDb
+        } else {
+            Self::MySql
+        }
+    }
+
+    /// Returns the statement that limits the execution time of the session's statements,
+    /// or removes the limit when `timeout` is `None`
+    pub fn session_timeout_sql(self, timeout: Option<Duration>) -> String {
+        let timeout = timeout.unwrap_or_default();
+        match self {
+            Self::MySql => format!("SET SESSION max_execution_time = {}", timeout.as_millis()),
+            Self::MariaDb => format!("SET SESSION max_statement_time = {:.3}", timeout.as_secs_f64()),
+        }
+    }
+}
+
+/// Limits the execution time of statements run on a connection, or removes the limit
+///
+/// This is the server's own safeguard: it stops statements even if the client cannot reach
+/// the server to cancel them.
+///
+/// # Errors
+///
+/// Returns the driver error if the server version cannot be read or the variable cannot be set.
+pub fn set_session_timeout<Q: Queryable>(conn: &mut Q, timeout: Option<Duration>) -> mysql::Result<()> {
+    let version: Option<String> = conn.query_first("SELECT VERSION()")?;
+    let flavor = ServerFlavor::from_version(version.as_deref().unwrap_or_default());
+    conn.query_drop(flavor.session_timeout_sql(timeout))
+}
+
+/// Cancels the running query of a connection once a deadline passes
+///
+/// When the timeout expires, `KILL QUERY <connection_id>` is sent from a second connection of
+/// the pool. The statement fails with an interrupted error and the connection stays usable.
+/// Dropping the watchdog stops it, waiting for a cancellation that is under way so it cannot
+/// reach a later query.
+pub struct QueryWatchdog {
+    expired: Arc<AtomicBool>,
+    done: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl QueryWatchdog {
+    /// Starts watching the connection with the given id
+    pub fn start(pool: &Pool, connection_id: u32, timeout: Duration) -> Self {
+        let expired = Arc::new(AtomicBool::new(false));
+        let (done, finished) = mpsc::channel::<()>();
+        let thread = {
+            let expired = Arc::clone(&expired);
+            let pool = pool.clone();
+            std::thread::spawn(move || {
+                if finished.recv_timeout(timeout) != Err(RecvTimeoutError::Timeout) {
+                    return;
+                }
+                expired.store(true, Ordering::SeqCst);
+                let killed = pool
+                    .get_conn()
+                    .and_then(|mut conn| conn.query_drop(format!("KILL QUERY {}", connection_id)));
+                if let Err(e) = killed {
+                    eprintln!("Warning: failed to cancel the query after {}: {}", format_duration(timeout), e);
+                }
+            })
+        };
+        Self {
+            expired,
+            done: Some(done),
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns true once the deadline has passed and cancelling the query has begun
+    pub fn expired(&self) -> bool {
+        self.expired.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for QueryWatchdog {
+    fn drop(&mut self) {
+        drop(self.done.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("1500ms"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("5M"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+
+        for value in ["", "0", "0s", "-5", "1.5s", "10d", "s"] {
+            assert!(parse_duration(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(30)), "30s");
+        assert_eq!(format_duration(Duration::from_millis(1500)), "1500ms");
+    }
+
+    #[test]
+    fn test_session_timeout_sql() {
+        assert_eq!(ServerFlavor::from_version("8.0.36"), ServerFlavor::MySql);
+        assert_eq!(ServerFlavor::from_version("10.11.6-MariaDB-1:10.11.6+maria~ubu2204"), ServerFlavor::MariaDb);
+
+        let timeout = Some(Duration::from_millis(1500));
+        assert_eq!(ServerFlavor::MySql.session_timeout_sql(timeout), "SET SESSION max_execution_time = 1500");
+        assert_eq!(ServerFlavor::MariaDb.session_timeout_sql(timeout), "SET SESSION max_statement_time = 1.500");
+        assert_eq!(ServerFlavor::MySql.session_timeout_sql(None), "SET SESSION max_execution_time = 0");
+        assert_eq!(ServerFlavor::MariaDb.session_timeout_sql(None), "SET SESSION max_statement_time = 0.000");
+    }
+}
@@ -1,8 +1,17 @@
 use anyhow::Result;
-use mysql::{Pool, SslOpts};
+use mysql::{Pool, PoolConstraints, SslOpts};
 use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::retry::{Operation, RetryPolicy};
+
+/// Pool size limits: an export uses one connection, plus one to cancel a timed out query
+///
+/// The driver's default opens ten connections up front, each of which would repeat the TLS
+/// handshake and wait out `--connect-timeout` on every connection attempt.
+const POOL_CONSTRAINTS: PoolConstraints = PoolConstraints::new_const::<1, 10>();
+
 use rustls::pki_types::CertificateDer;
 
 /// TLS-specific error types for better error handling and user guidance
@@ -412,6 +421,21 @@ pub fn create_tls_connection(
     database_url: &str,
     tls_config: Option<TlsConfig>,
     verbose: bool,
+) -> Result<Pool, TlsError> {
+    create_tls_connection_with_retry(database_url, tls_config, verbose, None, &RetryPolicy::default())
+}
+
+/// Creates a MySQL connection pool with rustls-only TLS configuration, retrying transient failures
+///
+/// `connect_timeout` limits how long each TCP connection may take to establish. Connecting is
+/// retried as `retry` allows when the server cannot be reached. The pool opens a single
+/// connection up front unless the URL sets `pool_min`.
+pub fn create_tls_connection_with_retry(
+    database_url: &str,
+    tls_config: Option<TlsConfig>,
+    verbose: bool,
+    connect_timeout: Option<Duration>,
+    retry: &RetryPolicy,
 ) -> Result<Pool, TlsError> {
     use mysql::{Opts, OptsBuilder};
 
@@ -419,7 +443,12 @@ pub fn create_tls_connection(
     let opts = Opts::from_url(database_url)
         .map_err(|e| TlsError::connection_failed(format!("Invalid database URL format: {}", e)))?;
 
+    // Keep pool_min and pool_max if the URL sets them
+    let pool_opts = opts.get_pool_opts().clone();
     let mut opts_builder = OptsBuilder::from_opts(opts);
+    if pool_opts.constraints() == PoolConstraints::DEFAULT {
+        opts_builder = opts_builder.pool_opts(pool_opts.with_constraints(POOL_CONSTRAINTS));
+    }
 
     // Apply TLS configuration if provided
     if let Some(config) = tls_config {
@@ -466,8 +495,12 @@ pub fn create_tls_connection(
         }
     }
 
+    if connect_timeout.is_some() {
+        opts_builder = opts_builder.tcp_connect_timeout(connect_timeout);
+    }
+
     // Create the connection pool with enhanced error handling
-    Pool::new(opts_builder).map_err(|mysql_error| {
+    retry.run(Operation::Connect, || Pool::new(opts_builder.clone())).map_err(|mysql_error| {
         // Classify MySQL errors and provide appropriate TLS error with guidance
         let error_string = mysql_error.to_string();
         let error_lower = error_string.to_lowercase();
@@ -0,0 +1,129 @@
+use mysql::Column;
+use mysql::consts::{ColumnFlags, ColumnType};
+
+/// MySQL character set id used for binary strings (BINARY, VARBINARY, BLOB)
+const BINARY_CHARSET: u16 = 63;
+
+/// Logical kind of a result column, derived from MySQL column metadata
+///
+/// Writers use this to pick a typed representation instead of guessing from the
+/// text of each value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    /// Signed integer types (TINYINT through BIGINT, YEAR)
+    Integer,
+    /// Integer types with the UNSIGNED flag
+    UnsignedInteger,
+    /// FLOAT and DOUBLE
+    Float,
+    /// DECIMAL and NUMERIC, which must be kept exact
+    Decimal,
+    /// DATE
+    Date,
+    /// DATETIME and TIMESTAMP
+    DateTime,
+    /// TIME
+    Time,
+    /// Character data (CHAR, VARCHAR, TEXT, ENUM, SET, JSON)
+    Text,
+    /// Binary data (BINARY, VARBINARY, BLOB, BIT, GEOMETRY)
+    Binary,
+}
+
+impl ColumnKind {
+    /// Classifies a column from its type, flags and character set
+    pub fn from_column(column: &Column) -> Self {
+        let unsigned = column.flags().contains(ColumnFlags::UNSIGNED_FLAG);
+
+        match column.column_type() {
+            ColumnType::MYSQL_TYPE_TINY
+            | ColumnType::MYSQL_TYPE_SHORT
+            | ColumnType::MYSQL_TYPE_INT24
+            | ColumnType::MYSQL_TYPE_LONG
+            | ColumnType::MYSQL_TYPE_LONGLONG => {
+                if unsigned {
+                    Self::UnsignedInteger
+                } else {
+                    Self::Integer
+                }
+            },
+            ColumnType::MYSQL_TYPE_YEAR => Self::Integer,
+            ColumnType::MYSQL_TYPE_FLOAT | ColumnType::MYSQL_TYPE_DOUBLE => Self::Float,
+            ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL => Self::Decimal,
+            ColumnType::MYSQL_TYPE_DATE | ColumnType::MYSQL_TYPE_NEWDATE => Self::Date,
+            ColumnType::MYSQL_TYPE_DATETIME
+            | ColumnType::MYSQL_TYPE_DATETIME2
+            | ColumnType::MYSQL_TYPE_TIMESTAMP
+            | ColumnType::MYSQL_TYPE_TIMESTAMP2 => Self::DateTime,
+            ColumnType::MYSQL_TYPE_TIME | ColumnType::MYSQL_TYPE_TIME2 => Self::Time,
+            ColumnType::MYSQL_TYPE_BIT | ColumnType::MYSQL_TYPE_GEOMETRY => Self::Binary,
+            ColumnType::MYSQL_TYPE_VARCHAR
+            | ColumnType::MYSQL_TYPE_VAR_STRING
+            | ColumnType::MYSQL_TYPE_STRING
+            | ColumnType::MYSQL_TYPE_TINY_BLOB
+            | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+            | ColumnType::MYSQL_TYPE_LONG_BLOB
+            | ColumnType::MYSQL_TYPE_BLOB
+                if column.character_set() == BINARY_CHARSET =>
+            {
+                Self::Binary
+            },
+            _ => Self::Text,
+        }
+    }
+
+    /// Returns true for kinds whose values are numbers
+    pub fn is_numeric(self) -> bool {
+        matches!(self, Self::Integer | Self::UnsignedInteger | Self::Float | Self::Decimal)
+    }
+}
+
+/// Classifies every column of a result set
+pub fn column_kinds(columns: &[Column]) -> Vec<ColumnKind> {
+    columns.iter().map(ColumnKind::from_column).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(column_type: ColumnType) -> Column {
+        Column::new(column_type).with_character_set(33)
+    }
+
+    #[test]
+    fn test_integer_kinds() {
+        assert_eq!(ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_LONG)), ColumnKind::Integer);
+        assert_eq!(
+            ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_LONGLONG).with_flags(ColumnFlags::UNSIGNED_FLAG)),
+            ColumnKind::UnsignedInteger
+        );
+        assert_eq!(ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_YEAR)), ColumnKind::Integer);
+    }
+
+    #[test]
+    fn test_decimal_and_float_kinds() {
+        assert_eq!(ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_NEWDECIMAL)), ColumnKind::Decimal);
+        assert_eq!(ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_DOUBLE)), ColumnKind::Float);
+        assert!(ColumnKind::Decimal.is_numeric());
+        assert!(!ColumnKind::Text.is_numeric());
+    }
+
+    #[test]
+    fn test_text_and_binary_kinds() {
+        assert_eq!(ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_VAR_STRING)), ColumnKind::Text);
+        assert_eq!(ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_BLOB)), ColumnKind::Text);
+        assert_eq!(
+            ColumnKind::from_column(&Column::new(ColumnType::MYSQL_TYPE_BLOB).with_character_set(BINARY_CHARSET)),
+            ColumnKind::Binary
+        );
+        assert_eq!(ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_BIT)), ColumnKind::Binary);
+    }
+
+    #[test]
+    fn test_temporal_kinds() {
+        assert_eq!(ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_DATE)), ColumnKind::Date);
+        assert_eq!(ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_TIMESTAMP)), ColumnKind::DateTime);
+        assert_eq!(ColumnKind::from_column(&column(ColumnType::MYSQL_TYPE_TIME)), ColumnKind::Time);
+    }
+}
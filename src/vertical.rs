@@ -0,0 +1,106 @@
+use std::io::{BufWriter, Write};
+
+use anyhow::Result;
+use mysql::Value;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{FormatWriter, OUTPUT_BUFFER_CAPACITY, mysql_value_to_string};
+
+/// Vertical record writer that implements the FormatWriter trait
+///
+/// Equivalent to ending a query with `\G` in the mysql client: each row is written as a
+/// numbered block with one `column: value` line per column, with the column names
+/// right-aligned. Rows are written as they arrive, so wide rows can be read in a terminal
+/// without buffering the result.
+pub struct VerticalWriter<W: Write> {
+    writer: BufWriter<W>,
+    null_marker: String,
+    labels: Vec<String>,
+    rows: u64,
+}
+
+impl<W: Write> VerticalWriter<W> {
+    /// Creates a new VerticalWriter over the specified writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::with_capacity(OUTPUT_BUFFER_CAPACITY, writer),
+            null_marker: "NULL".to_string(),
+            labels: Vec::new(),
+            rows: 0,
+        }
+    }
+
+    /// Sets the text shown for SQL NULL values (`NULL` by default)
+    pub fn with_null_marker(mut self, null_marker: impl Into<String>) -> Self {
+        self.null_marker = null_marker.into();
+        self
+    }
+
+    /// Writes one numbered record block
+    fn write_record<'a>(&mut self, fields: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        self.rows += 1;
+        writeln!(self.writer, "{0} {1}. row {0}", "*".repeat(27), self.rows)?;
+        for (label, field) in self.labels.iter().zip(fields) {
+            writeln!(self.writer, "{}: {}", label, field)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> FormatWriter for VerticalWriter<W> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        let width = columns.iter().map(|column| column.width()).max().unwrap_or(0);
+        self.labels = columns
+            .iter()
+            .map(|column| format!("{}{}", " ".repeat(width - column.width()), column))
+            .collect();
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        self.write_record(row.iter().map(String::as_str))
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        let mut row = Vec::with_capacity(values.len());
+        for value in values {
+            match value {
+                Value::NULL => row.push(self.null_marker.clone()),
+                _ => row.push(mysql_value_to_string(value)?),
+            }
+        }
+        self.write_row(&row)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vertical_records() {
+        let mut output = Vec::new();
+        let mut writer = VerticalWriter::new(&mut output);
+        writer.write_header(&["id".to_string(), "email".to_string()]).unwrap();
+        writer
+            .write_values(&[Value::Int(1), Value::Bytes(b"a@example.com".to_vec())])
+            .unwrap();
+        writer.write_values(&[Value::Int(2), Value::NULL]).unwrap();
+        writer.finalize().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "*************************** 1. row ***************************\n\
+             \x20  id: 1\n\
+             email: a@example.com\n\
+             *************************** 2. row ***************************\n\
+             \x20  id: 2\n\
+             email: NULL\n"
+        );
+    }
+}
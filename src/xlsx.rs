@@ -0,0 +1,374 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use mysql::{Column, Value};
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+
+use crate::temporal::{MICROS_PER_DAY, temporal_parts};
+use crate::types::{ColumnKind, column_kinds};
+use crate::{FormatWriter, mysql_value_to_string};
+
+/// Rows available for data on each sheet (Excel's 1,048,576-row limit minus the header)
+const SHEET_DATA_ROWS: u32 = 1_048_575;
+
+/// Longest string Excel accepts in a single cell
+const MAX_CELL_CHARS: usize = 32_767;
+
+/// Largest integer magnitude Excel stores without losing digits (15 significant digits)
+const MAX_EXACT_INTEGER: u128 = 999_999_999_999_999;
+
+/// Excel serial number of 1970-01-01
+const UNIX_EPOCH_SERIAL: f64 = 25_569.0;
+
+/// Excel serial number of 1900-03-01; earlier serials are affected by the 1900 leap-year bug
+const MIN_DATE_SERIAL: f64 = 61.0;
+
+/// Column width bounds, in characters
+const MIN_COLUMN_WIDTH: usize = 8;
+const MAX_COLUMN_WIDTH: usize = 60;
+
+/// Excel workbook writer that implements the FormatWriter trait
+///
+/// Cells are typed from MySQL column metadata: integers, floats and DECIMAL values become
+/// numeric cells, DATE/DATETIME/TIMESTAMP become date cells, and everything else is written
+/// as text so leading zeros survive. Integers and decimals with more than 15 significant
+/// digits are written as text as well, because Excel would silently round them. The header
+/// row is bold and frozen, columns are sized to their content, and when a sheet reaches
+/// Excel's row limit the output continues on a new sheet with the header repeated.
+///
+/// Rows are flushed to temporary files as they are written (constant memory mode); the
+/// workbook is assembled and written to the output by [`FormatWriter::finish`].
+pub struct XlsxWriter<W: Write + Send> {
+    output: Option<W>,
+    workbook: Workbook,
+    header: Vec<String>,
+    kinds: Vec<ColumnKind>,
+    widths: Vec<usize>,
+    sheet: Option<usize>,
+    row: u32,
+    sheet_rows: u32,
+    bold: Format,
+    date: Format,
+    datetime: Format,
+}
+
+/// A value converted to an Excel cell
+#[derive(Debug, PartialEq)]
+enum Cell {
+    Empty,
+    Number(f64),
+    Text(String),
+    Date(f64),
+    DateTime(f64),
+}
+
+impl<W: Write + Send> XlsxWriter<W> {
+    /// Creates a new XlsxWriter over the specified writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            output: Some(writer),
+            workbook: Workbook::new(),
+            header: Vec::new(),
+            kinds: Vec::new(),
+            widths: Vec::new(),
+            sheet: None,
+            row: 0,
+            sheet_rows: SHEET_DATA_ROWS,
+            bold: Format::new().set_bold(),
+            date: Format::new().set_num_format("yyyy-mm-dd"),
+            datetime: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+        }
+    }
+
+    fn worksheet(&mut self) -> Result<&mut Worksheet> {
+        let index = self.sheet.context("XLSX header must be written before rows")?;
+        Ok(self.workbook.worksheet_from_index(index)?)
+    }
+
+    /// Starts a new sheet with a bold, frozen header row
+    fn add_sheet(&mut self) -> Result<()> {
+        self.apply_widths()?;
+
+        let index = self.sheet.map_or(0, |index| index + 1);
+        self.workbook.add_worksheet_with_constant_memory();
+        self.sheet = Some(index);
+        self.row = 0;
+        self.widths = self.header.iter().map(|name| name.chars().count()).collect();
+
+        let header = self.header.clone();
+        let bold = self.bold.clone();
+        let worksheet = self.worksheet()?;
+        for (col, name) in header.iter().enumerate() {
+            worksheet.write_string_with_format(0, column_number(col)?, truncate(name), &bold)?;
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+        Ok(())
+    }
+
+    /// Sizes the columns of the current sheet to the longest value written to them
+    fn apply_widths(&mut self) -> Result<()> {
+        if self.sheet.is_none() {
+            return Ok(());
+        }
+        let widths = self.widths.clone();
+        let worksheet = self.worksheet()?;
+        for (col, width) in widths.into_iter().enumerate() {
+            let width = (width + 2).clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+            worksheet.set_column_width(column_number(col)?, width as f64)?;
+        }
+        Ok(())
+    }
+
+    /// Writes one data row of cells, rolling over to a new sheet when the current one is full
+    fn write_cells(&mut self, cells: Vec<Cell>) -> Result<()> {
+        if self.sheet.is_none() || self.row >= self.sheet_rows {
+            self.add_sheet()?;
+        }
+        self.row += 1;
+
+        let row = self.row;
+        let (date, datetime) = (self.date.clone(), self.datetime.clone());
+        let mut widths = std::mem::take(&mut self.widths);
+        let worksheet = self.worksheet()?;
+        for (col, cell) in cells.into_iter().enumerate() {
+            let col_number = column_number(col)?;
+            let width = match cell {
+                Cell::Empty => 0,
+                Cell::Number(number) => {
+                    worksheet.write_number(row, col_number, number)?;
+                    number.to_string().len()
+                },
+                Cell::Text(text) => {
+                    let text = truncate(&text);
+                    worksheet.write_string(row, col_number, text)?;
+                    text.chars().count()
+                },
+                Cell::Date(serial) => {
+                    worksheet.write_number_with_format(row, col_number, serial, &date)?;
+                    10
+                },
+                Cell::DateTime(serial) => {
+                    worksheet.write_number_with_format(row, col_number, serial, &datetime)?;
+                    19
+                },
+            };
+            if let Some(max) = widths.get_mut(col) {
+                *max = (*max).max(width);
+            }
+        }
+        self.widths = widths;
+        Ok(())
+    }
+
+    fn kind(&self, index: usize) -> ColumnKind {
+        self.kinds.get(index).copied().unwrap_or(ColumnKind::Text)
+    }
+}
+
+impl<W: Write + Send> FormatWriter for XlsxWriter<W> {
+    fn write_header(&mut self, columns: &[String]) -> Result<()> {
+        self.header = columns.to_vec();
+        self.add_sheet()
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        let cells = row.iter().map(|field| Cell::Text(field.clone())).collect();
+        self.write_cells(cells)
+    }
+
+    fn write_columns(&mut self, columns: &[Column]) -> Result<()> {
+        self.kinds = column_kinds(columns);
+        self.write_header(&crate::column_names(columns))
+    }
+
+    fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        let cells = values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| cell_for(self.kind(index), value))
+            .collect::<Result<Vec<_>>>()?;
+        self.write_cells(cells)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let Some(output) = self.output.take() else {
+            return Ok(());
+        };
+        if self.sheet.is_none() {
+            self.add_sheet()?;
+        }
+        self.apply_widths()?;
+        self.workbook.save_to_writer(output)?;
+        Ok(())
+    }
+}
+
+/// Converts a MySQL value to a cell according to its column kind
+fn cell_for(kind: ColumnKind, value: &Value) -> Result<Cell> {
+    if let Value::NULL = value {
+        return Ok(Cell::Empty);
+    }
+
+    let text = || mysql_value_to_string(value).map(Cell::Text);
+    match kind {
+        ColumnKind::Integer | ColumnKind::UnsignedInteger => {
+            let integer = match value {
+                Value::Int(i) => Some(i128::from(*i)),
+                Value::UInt(u) => Some(i128::from(*u)),
+                _ => mysql_value_to_string(value)?.trim().parse::<i128>().ok(),
+            };
+            match integer {
+                Some(integer) if integer.unsigned_abs() <= MAX_EXACT_INTEGER => Ok(Cell::Number(integer as f64)),
+                _ => text(),
+            }
+        },
+        ColumnKind::Float => {
+            let float = match value {
+                Value::Float(f) => Some(f64::from(*f)),
+                Value::Double(d) => Some(*d),
+                _ => mysql_value_to_string(value)?.trim().parse::<f64>().ok(),
+            };
+            match float {
+                Some(float) if float.is_finite() => Ok(Cell::Number(float)),
+                _ => text(),
+            }
+        },
+        ColumnKind::Decimal => {
+            let decimal = mysql_value_to_string(value)?;
+            let digits = decimal
+                .bytes()
+                .filter(u8::is_ascii_digit)
+                .skip_while(|b| *b == b'0')
+                .count();
+            match decimal.trim().parse::<f64>() {
+                Ok(number) if digits <= 15 && number.is_finite() => Ok(Cell::Number(number)),
+                _ => Ok(Cell::Text(decimal)),
+            }
+        },
+        ColumnKind::Date | ColumnKind::DateTime => match temporal_parts(value)? {
+            Some((days, micros)) => {
+                let serial = UNIX_EPOCH_SERIAL + days as f64 + micros as f64 / MICROS_PER_DAY as f64;
+                if serial < MIN_DATE_SERIAL {
+                    text()
+                } else if kind == ColumnKind::Date {
+                    Ok(Cell::Date(serial))
+                } else {
+                    Ok(Cell::DateTime(serial))
+                }
+            },
+            None => text(),
+        },
+        ColumnKind::Time | ColumnKind::Text | ColumnKind::Binary => text(),
+    }
+}
+
+/// Converts a column index to an Excel column number
+fn column_number(index: usize) -> Result<u16> {
+    u16::try_from(index).context("Too many columns for an XLSX worksheet")
+}
+
+/// Truncates text to the longest string Excel accepts in a cell
+fn truncate(text: &str) -> &str {
+    match text.char_indices().nth(MAX_CELL_CHARS) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mysql::consts::{ColumnFlags, ColumnType};
+    use std::io::{Cursor, Read};
+
+    fn text(value: &str) -> Value {
+        Value::Bytes(value.as_bytes().to_vec())
+    }
+
+    /// Returns the XML of every worksheet in a workbook, in order
+    fn sheets(workbook: Vec<u8>) -> Vec<String> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(workbook)).unwrap();
+        let mut sheets = Vec::new();
+        for index in 1.. {
+            let Ok(mut file) = archive.by_name(&format!("xl/worksheets/sheet{}.xml", index)) else {
+                break;
+            };
+            let mut xml = String::new();
+            file.read_to_string(&mut xml).unwrap();
+            sheets.push(xml);
+        }
+        sheets
+    }
+
+    #[test]
+    fn test_cell_types_follow_column_kinds() {
+        assert_eq!(cell_for(ColumnKind::Integer, &text("42")).unwrap(), Cell::Number(42.0));
+        assert_eq!(
+            cell_for(ColumnKind::UnsignedInteger, &text("18446744073709551615")).unwrap(),
+            Cell::Text("18446744073709551615".to_string())
+        );
+        assert_eq!(cell_for(ColumnKind::Text, &text("00501")).unwrap(), Cell::Text("00501".to_string()));
+        assert_eq!(cell_for(ColumnKind::Decimal, &text("-12.50")).unwrap(), Cell::Number(-12.5));
+        assert_eq!(
+            cell_for(ColumnKind::Decimal, &text("12345678901234.5678")).unwrap(),
+            Cell::Text("12345678901234.5678".to_string())
+        );
+        assert_eq!(cell_for(ColumnKind::Float, &Value::Double(2.5)).unwrap(), Cell::Number(2.5));
+        assert_eq!(cell_for(ColumnKind::Date, &text("1970-01-02")).unwrap(), Cell::Date(25_570.0));
+        assert_eq!(cell_for(ColumnKind::DateTime, &text("1970-01-01 12:00:00")).unwrap(), Cell::DateTime(25_569.5));
+        assert_eq!(cell_for(ColumnKind::Date, &text("0000-00-00")).unwrap(), Cell::Text("0000-00-00".to_string()));
+        assert_eq!(cell_for(ColumnKind::Integer, &Value::NULL).unwrap(), Cell::Empty);
+    }
+
+    #[test]
+    fn test_xlsx_header_is_bold_and_frozen() {
+        let columns = [
+            Column::new(ColumnType::MYSQL_TYPE_LONGLONG)
+                .with_name(b"id")
+                .with_flags(ColumnFlags::UNSIGNED_FLAG),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+                .with_name(b"zip")
+                .with_character_set(33),
+        ];
+
+        let mut output = Vec::new();
+        let mut writer = XlsxWriter::new(&mut output);
+        writer.write_columns(&columns).unwrap();
+        writer.write_values(&[Value::UInt(7), text("00501")]).unwrap();
+        writer.finalize().unwrap();
+
+        let sheets = sheets(output);
+        assert_eq!(sheets.len(), 1);
+        assert!(sheets[0].contains(r#"<pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/>"#));
+        assert!(sheets[0].contains(r#"<c r="A2"><v>7</v></c>"#));
+        assert!(sheets[0].contains(r#"<c r="B2" t="inlineStr"><is><t>00501</t></is></c>"#));
+        assert!(sheets[0].contains("<cols>"));
+    }
+
+    #[test]
+    fn test_xlsx_rolls_over_to_new_sheets() {
+        let mut output = Vec::new();
+        let mut writer = XlsxWriter::new(&mut output);
+        writer.sheet_rows = 2;
+        writer.write_header(&["name".to_string()]).unwrap();
+        for name in ["a", "b", "c", "d", "e"] {
+            writer.write_row(&[name.to_string()]).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let sheets = sheets(output);
+        assert_eq!(sheets.len(), 3);
+        for sheet in &sheets {
+            assert!(sheet.contains("<t>name</t>"));
+        }
+        assert!(sheets[2].contains("<t>e</t>"));
+        assert!(!sheets[2].contains("<t>d</t>"));
+    }
+
+    #[test]
+    fn test_truncate_to_cell_limit() {
+        let long = "é".repeat(MAX_CELL_CHARS + 5);
+        assert_eq!(truncate(&long).chars().count(), MAX_CELL_CHARS);
+        assert_eq!(truncate("short"), "short");
+    }
+}